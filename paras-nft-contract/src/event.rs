@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::fmt::Display;
 
+use near_contract_standards::non_fungible_token::metadata::TokenMetadata;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
@@ -8,6 +10,7 @@ use serde_with::skip_serializing_none;
 #[serde(rename_all = "snake_case")]
 pub enum NearEvent {
     Nep171(Nep171Event),
+    Paras(ParasEvent),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -25,6 +28,8 @@ pub enum Nep171EventKind {
     NftMint(Vec<NftMintData>),
     NftTransfer(Vec<NftTransferData>),
     NftBurn(Vec<NftBurnData>),
+    NftMetadataUpdate(Vec<NftMetadataUpdateData>),
+    ContractMetadataUpdate(Vec<ContractMetadataUpdateData>),
 }
 
 #[skip_serializing_none]
@@ -54,6 +59,265 @@ pub struct NftBurnData {
     pub memo: Option<String>,
 }
 
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftMetadataUpdateData {
+    pub token_ids: Vec<String>,
+    pub memo: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContractMetadataUpdateData {
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ParasEvent {
+    pub version: String,
+    #[serde(flatten)]
+    pub event_kind: ParasEventKind,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+pub enum ParasEventKind {
+    NftCreateSeries(NftCreateSeriesData),
+    NftSetSeriesPrice(NftSetSeriesPriceData),
+    NftDecreaseSeriesCopies(NftDecreaseSeriesCopiesData),
+    NftUpdateSeriesMetadata(NftUpdateSeriesMetadataData),
+    NftSetSeriesDutchAuction(NftSetSeriesDutchAuctionData),
+    NftSetSeriesSaleWindow(NftSetSeriesSaleWindowData),
+    NftSetSeriesMaxPerAccount(NftSetSeriesMaxPerAccountData),
+    NftSetSeriesTransferLock(NftSetSeriesTransferLockData),
+    NftSetSeriesExpiryPolicy(NftSetSeriesExpiryPolicyData),
+    NftSetSeriesMinterDelegation(NftSetSeriesMinterDelegationData),
+    NftSetSeriesPresalePrice(NftSetSeriesPresalePriceData),
+    NftAddSeriesAllowlist(NftAddSeriesAllowlistData),
+    NftRemoveSeriesAllowlist(NftRemoveSeriesAllowlistData),
+    NftSetTransactionFee(NftSetTransactionFeeData),
+    NftSetTreasury(NftSetTreasuryData),
+    NftApprove(NftApproveData),
+    NftRevoke(NftRevokeData),
+    NftApproveAll(NftOperatorData),
+    NftRevokeOperator(NftOperatorData),
+    NftStartAuction(NftStartAuctionData),
+    NftPlaceBid(NftPlaceBidData),
+    NftSettleAuction(NftSettleAuctionData),
+    NftSetSeriesRoyalty(NftSetSeriesRoyaltyData),
+    NftLockSeriesRoyalty(NftLockSeriesRoyaltyData),
+    FtPayoutFailed(FtPayoutData),
+    FtPayoutClaimed(FtPayoutData),
+    SetPaused(SetPausedData),
+    StageUpgrade(StageUpgradeData),
+    CancelUpgrade(UpgradeData),
+    DeployUpgrade(UpgradeData),
+    SetUpgradeDelay(SetUpgradeDelayData),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftCreateSeriesData {
+    pub token_series_id: String,
+    pub token_metadata: TokenMetadata,
+    pub creator_id: String,
+    pub price: Option<String>,
+    pub royalty: HashMap<String, u32>,
+    pub transaction_fee: String,
+    pub ft_token_id: Option<String>,
+    pub sale_start: Option<u32>,
+    pub sale_end: Option<u32>,
+    pub max_per_account: Option<u32>,
+    pub primary_split: HashMap<String, u32>,
+    pub transferable: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSetSeriesPriceData {
+    pub token_series_id: String,
+    pub price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_fee: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftDecreaseSeriesCopiesData {
+    pub token_series_id: String,
+    pub copies: String,
+    pub is_non_mintable: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftUpdateSeriesMetadataData {
+    pub token_series_id: String,
+    pub token_metadata: TokenMetadata,
+    pub is_metadata_frozen: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSetSeriesDutchAuctionData {
+    pub token_series_id: String,
+    pub start_price: String,
+    pub floor_price: String,
+    pub start_time: u32,
+    pub decay_interval: u32,
+    pub price_decrement: String,
+    pub transaction_fee: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSetSeriesSaleWindowData {
+    pub token_series_id: String,
+    pub sale_start: Option<u32>,
+    pub sale_end: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSetSeriesMaxPerAccountData {
+    pub token_series_id: String,
+    pub max_per_account: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSetSeriesTransferLockData {
+    pub token_series_id: String,
+    pub transfer_lock_sec: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSetSeriesExpiryPolicyData {
+    pub token_series_id: String,
+    pub expiry_policy: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSetSeriesMinterDelegationData {
+    pub token_series_id: String,
+    pub is_minter_delegated: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSetSeriesPresalePriceData {
+    pub token_series_id: String,
+    pub presale_price: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftAddSeriesAllowlistData {
+    pub token_series_id: String,
+    pub allowlist: HashMap<String, u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftRemoveSeriesAllowlistData {
+    pub token_series_id: String,
+    pub account_ids: Vec<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSetTransactionFeeData {
+    pub current_fee: u16,
+    pub next_fee: Option<u16>,
+    pub start_time: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSetTreasuryData {
+    pub treasury_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftApproveData {
+    pub token_id: String,
+    pub owner_id: String,
+    pub account_id: String,
+    pub approval_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftRevokeData {
+    pub token_id: String,
+    pub owner_id: String,
+    pub account_ids: Vec<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftOperatorData {
+    pub owner_id: String,
+    pub account_id: String,
+    pub token_series_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftStartAuctionData {
+    pub token_series_id: String,
+    pub reserve_price: String,
+    pub end_time: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftPlaceBidData {
+    pub token_series_id: String,
+    pub bidder_id: String,
+    pub amount: String,
+    pub end_time: u32,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSettleAuctionData {
+    pub token_series_id: String,
+    pub winner_id: Option<String>,
+    pub amount: Option<String>,
+    pub token_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftSetSeriesRoyaltyData {
+    pub token_series_id: String,
+    pub old_royalty: HashMap<String, u32>,
+    pub new_royalty: HashMap<String, u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NftLockSeriesRoyaltyData {
+    pub token_series_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FtPayoutData {
+    pub ft_token_id: String,
+    pub account_id: String,
+    pub amount: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetPausedData {
+    pub features: Vec<String>,
+    pub paused: bool,
+    pub account_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StageUpgradeData {
+    pub code_hash: String,
+    pub activates_at: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpgradeData {
+    pub code_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetUpgradeDelayData {
+    pub old_upgrade_delay_sec: u32,
+    pub new_upgrade_delay_sec: u32,
+}
+
 impl Display for NearEvent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&format!("EVENT_JSON:{}", self.to_json_string()))
@@ -69,6 +333,11 @@ impl NearEvent {
         NearEvent::new_171("1.0.0".to_string(), event_kind)
     }
 
+    /// metadata update events were added in NEP-171 1.1.0
+    pub fn new_171_v1_1(event_kind: Nep171EventKind) -> Self {
+        NearEvent::new_171("1.1.0".to_string(), event_kind)
+    }
+
     pub fn nft_burn(data: Vec<NftBurnData>) -> Self {
         NearEvent::new_171_v1(Nep171EventKind::NftBurn(data))
     }
@@ -80,6 +349,14 @@ impl NearEvent {
         NearEvent::new_171_v1(Nep171EventKind::NftMint(data))
     }
 
+    pub fn new_paras(version: String, event_kind: ParasEventKind) -> Self {
+        NearEvent::Paras(ParasEvent { version, event_kind })
+    }
+
+    pub fn new_paras_v1(event_kind: ParasEventKind) -> Self {
+        NearEvent::new_paras("1.0.0".to_string(), event_kind)
+    }
+
     pub(crate) fn to_json_string(&self) -> String {
         serde_json::to_string(self).unwrap()
     }
@@ -128,6 +405,284 @@ impl NearEvent {
     pub fn log_nft_burns(data: Vec<NftBurnData>) {
         NearEvent::nft_burn(data).log();
     }
+
+    pub fn log_nft_metadata_update(token_ids: Vec<String>, memo: Option<String>) {
+        NearEvent::new_171_v1_1(Nep171EventKind::NftMetadataUpdate(vec![NftMetadataUpdateData {
+            token_ids,
+            memo,
+        }]))
+        .log();
+    }
+
+    pub fn log_contract_metadata_update(memo: Option<String>) {
+        NearEvent::new_171_v1_1(Nep171EventKind::ContractMetadataUpdate(vec![
+            ContractMetadataUpdateData { memo },
+        ]))
+        .log();
+    }
+
+    pub fn log_nft_create_series(data: NftCreateSeriesData) {
+        NearEvent::new_paras_v1(ParasEventKind::NftCreateSeries(data)).log();
+    }
+
+    pub fn log_nft_set_series_price(
+        token_series_id: String,
+        price: Option<String>,
+        transaction_fee: Option<String>,
+    ) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSetSeriesPrice(NftSetSeriesPriceData {
+            token_series_id,
+            price,
+            transaction_fee,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_decrease_series_copies(token_series_id: String, copies: String, is_non_mintable: bool) {
+        NearEvent::new_paras_v1(ParasEventKind::NftDecreaseSeriesCopies(NftDecreaseSeriesCopiesData {
+            token_series_id,
+            copies,
+            is_non_mintable,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_update_series_metadata(
+        token_series_id: String,
+        token_metadata: TokenMetadata,
+        is_metadata_frozen: bool,
+    ) {
+        NearEvent::new_paras_v1(ParasEventKind::NftUpdateSeriesMetadata(NftUpdateSeriesMetadataData {
+            token_series_id,
+            token_metadata,
+            is_metadata_frozen,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_set_series_dutch_auction(data: NftSetSeriesDutchAuctionData) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSetSeriesDutchAuction(data)).log();
+    }
+
+    pub fn log_nft_set_series_sale_window(token_series_id: String, sale_start: Option<u32>, sale_end: Option<u32>) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSetSeriesSaleWindow(NftSetSeriesSaleWindowData {
+            token_series_id,
+            sale_start,
+            sale_end,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_set_series_max_per_account(token_series_id: String, max_per_account: Option<u32>) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSetSeriesMaxPerAccount(NftSetSeriesMaxPerAccountData {
+            token_series_id,
+            max_per_account,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_set_series_transfer_lock(token_series_id: String, transfer_lock_sec: Option<u32>) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSetSeriesTransferLock(NftSetSeriesTransferLockData {
+            token_series_id,
+            transfer_lock_sec,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_set_series_expiry_policy(token_series_id: String, expiry_policy: String) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSetSeriesExpiryPolicy(NftSetSeriesExpiryPolicyData {
+            token_series_id,
+            expiry_policy,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_set_series_minter_delegation(token_series_id: String, is_minter_delegated: bool) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSetSeriesMinterDelegation(NftSetSeriesMinterDelegationData {
+            token_series_id,
+            is_minter_delegated,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_set_series_presale_price(token_series_id: String, presale_price: Option<String>) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSetSeriesPresalePrice(NftSetSeriesPresalePriceData {
+            token_series_id,
+            presale_price,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_add_series_allowlist(token_series_id: String, allowlist: HashMap<String, u32>) {
+        NearEvent::new_paras_v1(ParasEventKind::NftAddSeriesAllowlist(NftAddSeriesAllowlistData {
+            token_series_id,
+            allowlist,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_remove_series_allowlist(token_series_id: String, account_ids: Vec<String>) {
+        NearEvent::new_paras_v1(ParasEventKind::NftRemoveSeriesAllowlist(NftRemoveSeriesAllowlistData {
+            token_series_id,
+            account_ids,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_set_transaction_fee(current_fee: u16, next_fee: Option<u16>, start_time: Option<u32>) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSetTransactionFee(NftSetTransactionFeeData {
+            current_fee,
+            next_fee,
+            start_time,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_set_treasury(treasury_id: String) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSetTreasury(NftSetTreasuryData { treasury_id })).log();
+    }
+
+    pub fn log_nft_approve(token_id: String, owner_id: String, account_id: String, approval_id: u64) {
+        NearEvent::new_paras_v1(ParasEventKind::NftApprove(NftApproveData {
+            token_id,
+            owner_id,
+            account_id,
+            approval_id,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_revoke(token_id: String, owner_id: String, account_ids: Vec<String>) {
+        NearEvent::new_paras_v1(ParasEventKind::NftRevoke(NftRevokeData {
+            token_id,
+            owner_id,
+            account_ids,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_approve_all(owner_id: String, account_id: String, token_series_id: Option<String>) {
+        NearEvent::new_paras_v1(ParasEventKind::NftApproveAll(NftOperatorData {
+            owner_id,
+            account_id,
+            token_series_id,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_revoke_operator(owner_id: String, account_id: String, token_series_id: Option<String>) {
+        NearEvent::new_paras_v1(ParasEventKind::NftRevokeOperator(NftOperatorData {
+            owner_id,
+            account_id,
+            token_series_id,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_start_auction(token_series_id: String, reserve_price: String, end_time: u32) {
+        NearEvent::new_paras_v1(ParasEventKind::NftStartAuction(NftStartAuctionData {
+            token_series_id,
+            reserve_price,
+            end_time,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_place_bid(token_series_id: String, bidder_id: String, amount: String, end_time: u32) {
+        NearEvent::new_paras_v1(ParasEventKind::NftPlaceBid(NftPlaceBidData {
+            token_series_id,
+            bidder_id,
+            amount,
+            end_time,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_settle_auction(
+        token_series_id: String,
+        winner_id: Option<String>,
+        amount: Option<String>,
+        token_id: Option<String>,
+    ) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSettleAuction(NftSettleAuctionData {
+            token_series_id,
+            winner_id,
+            amount,
+            token_id,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_set_series_royalty(
+        token_series_id: String,
+        old_royalty: HashMap<String, u32>,
+        new_royalty: HashMap<String, u32>,
+    ) {
+        NearEvent::new_paras_v1(ParasEventKind::NftSetSeriesRoyalty(NftSetSeriesRoyaltyData {
+            token_series_id,
+            old_royalty,
+            new_royalty,
+        }))
+        .log();
+    }
+
+    pub fn log_nft_lock_series_royalty(token_series_id: String) {
+        NearEvent::new_paras_v1(ParasEventKind::NftLockSeriesRoyalty(NftLockSeriesRoyaltyData {
+            token_series_id,
+        }))
+        .log();
+    }
+
+    pub fn log_ft_payout_failed(ft_token_id: String, account_id: String, amount: String) {
+        NearEvent::new_paras_v1(ParasEventKind::FtPayoutFailed(FtPayoutData {
+            ft_token_id,
+            account_id,
+            amount,
+        }))
+        .log();
+    }
+
+    pub fn log_ft_payout_claimed(ft_token_id: String, account_id: String, amount: String) {
+        NearEvent::new_paras_v1(ParasEventKind::FtPayoutClaimed(FtPayoutData {
+            ft_token_id,
+            account_id,
+            amount,
+        }))
+        .log();
+    }
+
+    pub fn log_set_paused(features: Vec<String>, paused: bool, account_id: String) {
+        NearEvent::new_paras_v1(ParasEventKind::SetPaused(SetPausedData {
+            features,
+            paused,
+            account_id,
+        }))
+        .log();
+    }
+
+    pub fn log_stage_upgrade(code_hash: String, activates_at: u32) {
+        NearEvent::new_paras_v1(ParasEventKind::StageUpgrade(StageUpgradeData {
+            code_hash,
+            activates_at,
+        }))
+        .log();
+    }
+
+    pub fn log_cancel_upgrade(code_hash: String) {
+        NearEvent::new_paras_v1(ParasEventKind::CancelUpgrade(UpgradeData { code_hash })).log();
+    }
+
+    pub fn log_deploy_upgrade(code_hash: String) {
+        NearEvent::new_paras_v1(ParasEventKind::DeployUpgrade(UpgradeData { code_hash })).log();
+    }
+
+    pub fn log_set_upgrade_delay(old_upgrade_delay_sec: u32, new_upgrade_delay_sec: u32) {
+        NearEvent::new_paras_v1(ParasEventKind::SetUpgradeDelay(SetUpgradeDelayData {
+            old_upgrade_delay_sec,
+            new_upgrade_delay_sec,
+        }))
+        .log();
+    }
 }
 
 #[cfg(test)]
@@ -252,4 +807,87 @@ mod tests {
             r#"{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"authorized_id":"4","old_owner_id":"alice","new_owner_id":"bob","token_ids":["2","3"],"memo":"has memo"},{"old_owner_id":"bob","new_owner_id":"alice","token_ids":["0","1"]}]}"#
         );
     }
+
+    #[test]
+    fn nft_place_bid() {
+        let log = NearEvent::new_paras_v1(ParasEventKind::NftPlaceBid(NftPlaceBidData {
+            token_series_id: "1".to_string(),
+            bidder_id: "bob".to_string(),
+            amount: "1000".to_string(),
+            end_time: 1618109222,
+        }))
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"paras","version":"1.0.0","event":"nft_place_bid","data":{"token_series_id":"1","bidder_id":"bob","amount":"1000","end_time":1618109222}}"#
+        );
+    }
+
+    #[test]
+    fn nft_settle_auction_without_bid() {
+        let log = NearEvent::new_paras_v1(ParasEventKind::NftSettleAuction(NftSettleAuctionData {
+            token_series_id: "1".to_string(),
+            winner_id: None,
+            amount: None,
+            token_id: None,
+        }))
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"paras","version":"1.0.0","event":"nft_settle_auction","data":{"token_series_id":"1"}}"#
+        );
+    }
+
+    #[test]
+    fn nft_set_series_price_not_for_sale() {
+        let log = NearEvent::new_paras_v1(ParasEventKind::NftSetSeriesPrice(NftSetSeriesPriceData {
+            token_series_id: "1".to_string(),
+            price: None,
+            transaction_fee: None,
+        }))
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"paras","version":"1.0.0","event":"nft_set_series_price","data":{"token_series_id":"1","price":null}}"#
+        );
+    }
+
+    #[test]
+    fn nft_set_transaction_fee() {
+        let log = NearEvent::new_paras_v1(ParasEventKind::NftSetTransactionFee(NftSetTransactionFeeData {
+            current_fee: 500,
+            next_fee: Some(100),
+            start_time: Some(1618109222),
+        }))
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"paras","version":"1.0.0","event":"nft_set_transaction_fee","data":{"current_fee":500,"next_fee":100,"start_time":1618109222}}"#
+        );
+    }
+
+    #[test]
+    fn nft_metadata_update() {
+        let log = NearEvent::new_171_v1_1(Nep171EventKind::NftMetadataUpdate(vec![NftMetadataUpdateData {
+            token_ids: vec!["1:1".to_string(), "1:2".to_string()],
+            memo: Some("{\"token_series_id\":\"1\"}".to_string()),
+        }]))
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nep171","version":"1.1.0","event":"nft_metadata_update","data":[{"token_ids":["1:1","1:2"],"memo":"{\"token_series_id\":\"1\"}"}]}"#
+        );
+    }
+
+    #[test]
+    fn contract_metadata_update() {
+        let log = NearEvent::new_171_v1_1(Nep171EventKind::ContractMetadataUpdate(vec![
+            ContractMetadataUpdateData { memo: None },
+        ]))
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nep171","version":"1.1.0","event":"contract_metadata_update","data":[{}]}"#
+        );
+    }
 }
\ No newline at end of file
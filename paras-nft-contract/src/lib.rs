@@ -1,17 +1,20 @@
-use near_contract_standards::non_fungible_token::core::{
-    NonFungibleTokenCore, NonFungibleTokenResolver,
-};
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_contract_standards::non_fungible_token::approval::NonFungibleTokenApproval;
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenResolver;
 use near_contract_standards::non_fungible_token::metadata::{
     NFTContractMetadata, NonFungibleTokenMetadataProvider, TokenMetadata, NFT_METADATA_SPEC,
 };
 use near_contract_standards::non_fungible_token::NonFungibleToken;
 use near_contract_standards::non_fungible_token::{Token, TokenId};
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, UnorderedMap, UnorderedSet};
-use near_sdk::json_types::{ValidAccountId, U128, U64};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet};
+use near_sdk::json_types::{Base64VecU8, ValidAccountId, U128, U64};
 use near_sdk::{
     assert_one_yocto, env, near_bindgen, serde_json::json, AccountId, Balance, BorshStorageKey,
-    PanicOnDefault, Promise, PromiseOrValue, Gas, ext_contract, Timestamp, serde_json::value::Value::Null
+    PanicOnDefault, Promise, PromiseOrValue, PromiseResult, Gas, ext_contract, StorageUsage, Timestamp
 };
 use near_sdk::serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -19,6 +22,9 @@ use near_sdk::env::is_valid_account_id;
 
 pub mod event;
 pub use event::NearEvent;
+use event::{NftBurnData, NftCreateSeriesData, NftMintData, NftSetSeriesDutchAuctionData};
+mod migration;
+use migration::{VersionedContract, STATE_VERSION, LEGACY_STATE_VERSION};
 
 /// between token_series_id and edition number e.g. 42:2 where 42 is series and 2 is edition
 pub const TOKEN_DELIMETER: char = ':';
@@ -31,8 +37,36 @@ const GAS_FOR_RESOLVE_TRANSFER: Gas = 10_000_000_000_000;
 const GAS_FOR_NFT_TRANSFER_CALL: Gas = 30_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER;
 const GAS_FOR_NFT_APPROVE: Gas = 10_000_000_000_000;
 const GAS_FOR_MINT: Gas = 90_000_000_000_000;
+const GAS_FOR_FT_TRANSFER: Gas = 10_000_000_000_000;
+const GAS_FOR_RESOLVE_FT_PAYOUT: Gas = 5_000_000_000_000;
+const GAS_FOR_UPGRADE_MIGRATE: Gas = 100_000_000_000_000;
 const NO_DEPOSIT: Balance = 0;
 const MAX_PRICE: Balance = 1_000_000_000 * 10u128.pow(24);
+/// bids placed within this window before the end extend the auction by the same amount
+const AUCTION_EXTENSION_SEC: TimestampSec = 10 * 60;
+/// next bid must be higher than the current highest bid by this fraction of 10_000
+const AUCTION_MIN_BID_INCREMENT: u128 = 500;
+/// covers the storage of the edition minted on settle, which is deducted from the proceeds
+const AUCTION_MIN_RESERVE_PRICE: Balance = 10u128.pow(23);
+/// editions per nft_mint_batch or nft_buy_batch, keeps the mint event under the log limit
+/// of a receipt and the call within the gas limit
+const MAX_MINT_BATCH_SIZE: u64 = 100;
+/// primary_split accounts of a series priced in fungible token, every payout is an ft_transfer
+/// with its own gas, together with creator and treasury they must fit in one ft_on_transfer
+const MAX_FT_PRIMARY_SPLIT_LEN: usize = 8;
+/// transfer_lock_sec of a series, the lock end must fit in a TimestampSec
+const MAX_TRANSFER_LOCK_SEC: TimestampSec = 10 * 365 * 24 * 60 * 60;
+/// token ids per nft_metadata_update log
+const METADATA_UPDATE_TOKEN_IDS_PER_LOG: u64 = 200;
+/// token ids logged by one call, together with the other logs of the call they must fit
+/// the log limit of a receipt. nft_log_series_metadata_update logs the rest
+const METADATA_UPDATE_TOKEN_IDS_PER_CALL: u64 = 600;
+/// storage of a storage_deposits entry for the longest account id, paid once on registration
+const STORAGE_BYTES_PER_ACCOUNT: u64 = 64 + 16 + 48;
+/// staged code can be deployed this long after stage_upgrade unless set_upgrade_delay is called
+const DEFAULT_UPGRADE_DELAY_SEC: TimestampSec = 24 * 60 * 60;
+/// set_upgrade_delay cannot go below this, integrators rely on seeing staged code in advance
+const MIN_UPGRADE_DELAY_SEC: TimestampSec = 6 * 60 * 60;
 
 pub type TokenSeriesId = String;
 pub type TimestampSec = u32;
@@ -67,6 +101,11 @@ pub trait NonFungibleTokenReceiver {
     );
 }
 
+#[ext_contract(ext_fungible_token)]
+trait FungibleTokenCore {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
 #[ext_contract(ext_self)]
 trait NonFungibleTokenResolver {
     fn nft_resolve_transfer(
@@ -76,6 +115,8 @@ trait NonFungibleTokenResolver {
         token_id: TokenId,
         approved_account_ids: Option<HashMap<AccountId, u64>>,
     ) -> bool;
+
+    fn ft_resolve_payout(&mut self, ft_token_id: AccountId, receiver_id: AccountId, amount: U128);
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -85,7 +126,79 @@ pub struct TokenSeries {
 	tokens: UnorderedSet<TokenId>,
     price: Option<Balance>,
     is_mintable: bool,
-    royalty: HashMap<AccountId, u32>
+    royalty: HashMap<AccountId, u32>,
+    // None means the series is priced in NEAR
+    ft_token_id: Option<AccountId>,
+    sale_start: Option<TimestampSec>,
+    sale_end: Option<TimestampSec>,
+    // presale before sale_start, allowlist holds the remaining allocation per account
+    presale_price: Option<Balance>,
+    allowlist: LookupMap<AccountId, u32>,
+    max_per_account: Option<u32>,
+    // when set, overrides price with a declining price curve
+    dutch_auction: Option<DutchAuction>,
+    // split of primary sale proceeds, empty means everything goes to creator_id
+    primary_split: HashMap<AccountId, u32>,
+    // locked by the contract owner, royalty can no longer be changed
+    is_royalty_locked: bool,
+    // frozen by the creator, metadata can no longer be updated
+    is_metadata_frozen: bool,
+    // false for soulbound series, tokens can only be burned
+    transferable: bool,
+    // tokens minted from now on cannot be transferred for this long after issued_at
+    transfer_lock_sec: Option<TimestampSec>,
+    // what happens to tokens once the expires_at copied from the series metadata passed
+    expiry_policy: ExpiryPolicy,
+    // set by the creator, minters can mint editions with nft_mint and nft_mint_batch
+    is_minter_delegated: bool,
+}
+
+/// fields of the series metadata that can be updated before the first edition is minted
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SeriesMetadataUpdate {
+    title: Option<String>,
+    description: Option<String>,
+    media: Option<String>,
+    media_hash: Option<Base64VecU8>,
+    extra: Option<String>,
+    reference: Option<String>,
+    reference_hash: Option<Base64VecU8>,
+}
+
+/// price drops by price_decrement every decay_interval seconds from start_time until floor_price
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DutchAuction {
+    pub start_price: U128,
+    pub floor_price: U128,
+    pub start_time: TimestampSec,
+    pub decay_interval: TimestampSec,
+    pub price_decrement: U128,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Bid {
+    bidder_id: AccountId,
+    amount: Balance,
+}
+
+/// English auction for the next edition of a series, paid in NEAR
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Auction {
+    reserve_price: Balance,
+    end_time: TimestampSec,
+    highest_bid: Option<Bid>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuctionJson {
+    token_series_id: TokenSeriesId,
+    reserve_price: U128,
+    end_time: TimestampSec,
+    highest_bidder_id: Option<AccountId>,
+    highest_bid: Option<U128>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -95,7 +208,28 @@ pub struct TokenSeriesJson {
 	metadata: TokenMetadata,
 	creator_id: AccountId,
     royalty: HashMap<AccountId, u32>,
-    transaction_fee: U128
+    transaction_fee: U128,
+    ft_token_id: Option<AccountId>,
+    sale_start: Option<TimestampSec>,
+    sale_end: Option<TimestampSec>,
+    presale_price: Option<U128>,
+    max_per_account: Option<u32>,
+    dutch_auction: Option<DutchAuction>,
+    primary_split: HashMap<AccountId, u32>,
+    is_royalty_locked: bool,
+    is_metadata_frozen: bool,
+    transferable: bool,
+    transfer_lock_sec: Option<TimestampSec>,
+    expiry_policy: ExpiryPolicy,
+    is_minter_delegated: bool,
+}
+
+/// msg of ft_transfer_call used to buy a series priced in fungible token
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftBuyArgs {
+    token_series_id: TokenSeriesId,
+    receiver_id: Option<ValidAccountId>,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
@@ -106,6 +240,88 @@ pub struct TransactionFee {
     pub current_fee: u16,
 }
 
+/// roles granted by the owner, the owner implicitly has every role
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    // mints editions of series whose creator delegated minting
+    Minter,
+    Pauser,
+    FeeManager,
+}
+
+/// features that can be paused independently by the owner or a pauser
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum PausableFeature {
+    SeriesCreation,
+    PrimarySales,
+    CreatorMints,
+    Transfers,
+    Burns,
+}
+
+impl PausableFeature {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PausableFeature::SeriesCreation => "series_creation",
+            PausableFeature::PrimarySales => "primary_sales",
+            PausableFeature::CreatorMints => "creator_mints",
+            PausableFeature::Transfers => "transfers",
+            PausableFeature::Burns => "burns",
+        }
+    }
+}
+
+/// handling of tokens past their expires_at
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryPolicy {
+    Transferable,
+    NonTransferable,
+    // non-transferable and burnable by the series creator
+    CreatorBurnable,
+}
+
+impl ExpiryPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExpiryPolicy::Transferable => "transferable",
+            ExpiryPolicy::NonTransferable => "non_transferable",
+            ExpiryPolicy::CreatorBurnable => "creator_burnable",
+        }
+    }
+}
+
+/// fields of the contract metadata that can be updated by the owner
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractMetadataUpdate {
+    base_uri: Option<String>,
+    icon: Option<String>,
+    reference: Option<String>,
+    reference_hash: Option<Base64VecU8>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct StagedUpgrade {
+    code_hash: Vec<u8>,
+    staged_at: TimestampSec,
+    activates_at: TimestampSec,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StagedUpgradeJson {
+    code_hash: String,
+    staged_at: TimestampSec,
+    activates_at: TimestampSec,
+}
+
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct MarketDataTransactionFee {
     pub transaction_fee: UnorderedMap<TokenSeriesId, u128>
@@ -113,16 +329,6 @@ pub struct MarketDataTransactionFee {
 
 near_sdk::setup_alloc!();
 
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-pub struct ContractV1 {
-    tokens: NonFungibleToken,
-    metadata: LazyOption<NFTContractMetadata>,
-    // CUSTOM
-	token_series_by_id: UnorderedMap<TokenSeriesId, TokenSeries>,
-    treasury_id: AccountId,
-    transaction_fee: TransactionFee
-}
-
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Contract {
@@ -132,7 +338,28 @@ pub struct Contract {
     token_series_by_id: UnorderedMap<TokenSeriesId, TokenSeries>,
     treasury_id: AccountId,
     transaction_fee: TransactionFee,
-    market_data_transaction_fee: MarketDataTransactionFee
+    market_data_transaction_fee: MarketDataTransactionFee,
+    // editions bought on public sale per (series, buyer)
+    series_mints_by_account: LookupMap<(TokenSeriesId, AccountId), u32>,
+    auction_by_series_id: LookupMap<TokenSeriesId, Auction>,
+    // NEP-145 storage balance, used when the attached deposit does not cover storage
+    storage_deposits: LookupMap<AccountId, Balance>,
+    role_members: LookupMap<Role, UnorderedSet<AccountId>>,
+    // set by propose_owner, becomes owner on accept_owner
+    pending_owner_id: Option<AccountId>,
+    paused_features: UnorderedSet<PausableFeature>,
+    // wasm staged by stage_upgrade, deployable once staged_upgrade.activates_at passed
+    staged_code: LazyOption<Vec<u8>>,
+    staged_upgrade: Option<StagedUpgrade>,
+    upgrade_delay_sec: TimestampSec,
+    // owner -> operator -> series the operator can transfer, None means every token of the owner
+    operator_approvals: LookupMap<AccountId, HashMap<AccountId, Option<Vec<TokenSeriesId>>>>,
+    // set at mint for series with transfer_lock_sec, removed on the first transfer after it passed
+    transfer_locked_until: LookupMap<TokenId, TimestampSec>,
+    // (ft_token_id, account_id) -> proceeds whose ft_transfer failed, claimable with claim_ft_payout
+    unclaimed_ft_payouts: LookupMap<(AccountId, AccountId), Balance>,
+    // account that paid the mint storage of a token and the bytes it paid for, refunded on burn
+    storage_paid_by_token_id: LookupMap<TokenId, (AccountId, StorageUsage)>,
 }
 
 const DATA_IMAGE_SVG_PARAS_ICON: &str = "data:image/svg+xml,%3Csvg width='1080' height='1080' viewBox='0 0 1080 1080' fill='none' xmlns='http://www.w3.org/2000/svg'%3E%3Crect width='1080' height='1080' rx='10' fill='%230000BA'/%3E%3Cpath fill-rule='evenodd' clip-rule='evenodd' d='M335.238 896.881L240 184L642.381 255.288C659.486 259.781 675.323 263.392 689.906 266.718C744.744 279.224 781.843 287.684 801.905 323.725C827.302 369.032 840 424.795 840 491.014C840 557.55 827.302 613.471 801.905 658.779C776.508 704.087 723.333 726.74 642.381 726.74H468.095L501.429 896.881H335.238ZM387.619 331.329L604.777 369.407C614.008 371.807 622.555 373.736 630.426 375.513C660.02 382.193 680.042 386.712 690.869 405.963C704.575 430.164 711.428 459.95 711.428 495.321C711.428 530.861 704.575 560.731 690.869 584.932C677.163 609.133 648.466 621.234 604.777 621.234H505.578L445.798 616.481L387.619 331.329Z' fill='white'/%3E%3C/svg%3E";
@@ -149,6 +376,18 @@ enum StorageKey {
     TokensBySeriesInner { token_series: String },
     TokensPerOwner { account_hash: Vec<u8> },
     MarketDataTransactionFee,
+    SeriesAllowlist { token_series: String },
+    SeriesMintsByAccount,
+    AuctionBySeriesId,
+    StorageDeposits,
+    RoleMembers,
+    RoleMembersInner { role: Role },
+    PausedFeatures,
+    StagedCode,
+    OperatorApprovals,
+    TransferLockedUntil,
+    UnclaimedFtPayouts,
+    StoragePaidByTokenId,
 }
 
 #[near_bindgen]
@@ -179,6 +418,7 @@ impl Contract {
         current_fee: u16,
     ) -> Self {
         metadata.assert_valid();
+        migration::write_state_version(STATE_VERSION);
         Self {
             tokens: NonFungibleToken::new(
                 StorageKey::NonFungibleToken,
@@ -198,40 +438,78 @@ impl Contract {
             market_data_transaction_fee: MarketDataTransactionFee{
                 transaction_fee: UnorderedMap::new(StorageKey::MarketDataTransactionFee)
             },
+            series_mints_by_account: LookupMap::new(StorageKey::SeriesMintsByAccount),
+            auction_by_series_id: LookupMap::new(StorageKey::AuctionBySeriesId),
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            role_members: LookupMap::new(StorageKey::RoleMembers),
+            pending_owner_id: None,
+            paused_features: UnorderedSet::new(StorageKey::PausedFeatures),
+            staged_code: LazyOption::new(StorageKey::StagedCode, None),
+            staged_upgrade: None,
+            upgrade_delay_sec: DEFAULT_UPGRADE_DELAY_SEC,
+            operator_approvals: LookupMap::new(StorageKey::OperatorApprovals),
+            transfer_locked_until: LookupMap::new(StorageKey::TransferLockedUntil),
+            unclaimed_ft_payouts: LookupMap::new(StorageKey::UnclaimedFtPayouts),
+            storage_paid_by_token_id: LookupMap::new(StorageKey::StoragePaidByTokenId),
         }
     }
 
+    /// migrates the stored state to the current layout, from_version is only used
+    /// when the state predates the stored version and defaults to LEGACY_STATE_VERSION.
+    /// Also called by the contract itself after deploy_upgrade, which may not change the layout
     #[init(ignore_state)]
-    pub fn migrate() -> Self {
-        let prev: ContractV1 = env::state_read().expect("ERR_NOT_INITIALIZED");
-        assert_eq!(
-            env::predecessor_account_id(),
-            prev.tokens.owner_id,
+    pub fn migrate(from_version: Option<u16>) -> Self {
+        let is_self_upgrade = env::predecessor_account_id() == env::current_account_id();
+        let version = match migration::read_state_version() {
+            Some(version) => {
+                assert!(
+                    version < STATE_VERSION || (version == STATE_VERSION && is_self_upgrade),
+                    "Paras: state is already at version {}",
+                    version
+                );
+                assert!(
+                    version == STATE_VERSION || migration::read_series_migration().is_none(),
+                    "Paras: token series migration is pending"
+                );
+                version
+            }
+            None => from_version.unwrap_or(LEGACY_STATE_VERSION),
+        };
+
+        let prev = VersionedContract::read(version);
+        assert!(
+            is_self_upgrade || &env::predecessor_account_id() == prev.owner_id(),
             "Paras: Only owner"
         );
 
-        let this = Contract {
-            tokens: prev.tokens,
-            metadata: prev.metadata,
-            token_series_by_id: prev.token_series_by_id,
-            treasury_id: prev.treasury_id,
-            transaction_fee: prev.transaction_fee,
-            market_data_transaction_fee: MarketDataTransactionFee{
-                transaction_fee: UnorderedMap::new(StorageKey::MarketDataTransactionFee)
-            },
-        };
+        let this = prev.into_current();
+        migration::write_state_version(STATE_VERSION);
 
         this
     }
 
+    pub fn get_state_version(&self) -> u16 {
+        migration::read_state_version().unwrap_or(STATE_VERSION)
+    }
+
+    /// rewrites up to limit series left in the previous layout by migrate, returns the number left.
+    /// Those series cannot be read until they are migrated
+    pub fn migrate_token_series(&mut self, limit: u64) -> U64 {
+        self.assert_owner();
+        migration::migrate_token_series(&self.token_series_by_id, limit).into()
+    }
+
+    pub fn get_token_series_to_migrate(&self) -> U64 {
+        migration::read_series_migration()
+            .map(|(from_index, to_index)| to_index - from_index)
+            .unwrap_or(0)
+            .into()
+    }
+
     #[payable]
     pub fn set_transaction_fee(&mut self, next_fee: u16, start_time: Option<TimestampSec>) {
         assert_one_yocto();
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.tokens.owner_id,
-            "Paras: Owner only"
-        );
+        self.assert_owner_or_role(Role::FeeManager);
 
         assert!(
             next_fee < 10_000,
@@ -242,7 +520,6 @@ impl Contract {
             self.transaction_fee.current_fee = next_fee;
             self.transaction_fee.next_fee = None;
             self.transaction_fee.start_time = None;
-            return
         } else {
             let start_time: TimestampSec = start_time.unwrap();
             assert!(
@@ -252,6 +529,12 @@ impl Contract {
             self.transaction_fee.next_fee = Some(next_fee);
             self.transaction_fee.start_time = Some(start_time);
         }
+
+        NearEvent::log_nft_set_transaction_fee(
+            self.transaction_fee.current_fee,
+            self.transaction_fee.next_fee,
+            self.transaction_fee.start_time,
+        );
     }
 
     pub fn calculate_new_market_data_transaction_fee(&mut self, token_series_id: &TokenSeriesId) -> u128{
@@ -292,26 +575,326 @@ impl Contract {
     #[payable]
     pub fn set_treasury(&mut self, treasury_id: ValidAccountId) {
         assert_one_yocto();
+        self.assert_owner_or_role(Role::Admin);
+        self.treasury_id = treasury_id.to_string();
+        NearEvent::log_nft_set_treasury(self.treasury_id.clone());
+    }
+
+    #[payable]
+    pub fn set_contract_metadata(&mut self, metadata: ContractMetadataUpdate) -> NFTContractMetadata {
+        assert!(
+            env::attached_deposit() >= 1,
+            "Paras: Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        self.assert_owner();
+
+        let mut contract_metadata = self.metadata.get().unwrap();
+        if metadata.base_uri.is_some() {
+            contract_metadata.base_uri = metadata.base_uri;
+        }
+        if metadata.icon.is_some() {
+            contract_metadata.icon = metadata.icon;
+        }
+        if metadata.reference.is_some() {
+            contract_metadata.reference = metadata.reference;
+        }
+        if metadata.reference_hash.is_some() {
+            contract_metadata.reference_hash = metadata.reference_hash;
+        }
+        contract_metadata.assert_valid();
+        self.metadata.set(&contract_metadata);
+
+        self.charge_storage_delta(initial_storage_usage);
+
+        NearEvent::log_contract_metadata_update(None);
+
+        contract_metadata
+    }
+
+    // Roles
+
+    #[payable]
+    pub fn grant_role(&mut self, role: Role, account_id: ValidAccountId) {
+        assert!(
+            env::attached_deposit() >= 1,
+            "Paras: Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        self.assert_owner();
+
+        let mut members = self.role_members.get(&role).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::RoleMembersInner { role }
+                    .try_to_vec()
+                    .unwrap(),
+            )
+        });
+        members.insert(account_id.as_ref());
+        self.role_members.insert(&role, &members);
+
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage), 0);
+    }
+
+    #[payable]
+    pub fn revoke_role(&mut self, role: Role, account_id: ValidAccountId) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+        self.assert_owner();
+
+        if let Some(mut members) = self.role_members.get(&role) {
+            members.remove(account_id.as_ref());
+            self.role_members.insert(&role, &members);
+        }
+
+        let freed_storage = initial_storage_usage.saturating_sub(env::storage_usage());
+        self.refund_storage(&env::predecessor_account_id(), env::storage_byte_cost() * Balance::from(freed_storage));
+    }
+
+    pub fn has_role(&self, role: Role, account_id: ValidAccountId) -> bool {
+        self.role_members
+            .get(&role)
+            .map(|members| members.contains(account_id.as_ref()))
+            .unwrap_or(false)
+    }
+
+    pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.role_members
+            .get(&role)
+            .map(|members| members.to_vec())
+            .unwrap_or_default()
+    }
+
+    // Ownership transfer, the new owner has to accept
+
+    #[payable]
+    pub fn propose_owner(&mut self, new_owner_id: ValidAccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.pending_owner_id = Some(new_owner_id.into());
+    }
+
+    #[payable]
+    pub fn accept_owner(&mut self) {
+        assert_one_yocto();
+        let pending_owner_id = self.pending_owner_id.take().expect("Paras: no pending owner");
+        assert_eq!(
+            env::predecessor_account_id(),
+            pending_owner_id,
+            "Paras: Pending owner only"
+        );
+        self.tokens.owner_id = pending_owner_id;
+    }
+
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
+
+    // Pause
+
+    #[payable]
+    pub fn set_paused(&mut self, features: Vec<PausableFeature>, paused: bool) {
+        assert_one_yocto();
+        self.assert_owner_or_role(Role::Pauser);
+
+        for feature in features.iter() {
+            if paused {
+                self.paused_features.insert(feature);
+            } else {
+                self.paused_features.remove(feature);
+            }
+        }
+
+        NearEvent::log_set_paused(
+            features.iter().map(|feature| feature.as_str().to_string()).collect(),
+            paused,
+            env::predecessor_account_id(),
+        );
+    }
+
+    pub fn is_paused(&self, feature: PausableFeature) -> bool {
+        self.paused_features.contains(&feature) || is_paused_by_series_migration(feature)
+    }
+
+    pub fn get_paused_features(&self) -> Vec<PausableFeature> {
+        self.paused_features.to_vec()
+    }
+
+    fn assert_not_paused(&self, feature: PausableFeature) {
+        assert!(
+            !self.paused_features.contains(&feature),
+            "Paras: {} is paused",
+            feature.as_str()
+        );
+        assert!(
+            !is_paused_by_series_migration(feature),
+            "Paras: {} is paused until token series migration is done",
+            feature.as_str()
+        );
+    }
+
+    // Upgrade
+
+    #[payable]
+    pub fn stage_upgrade(&mut self, code: Base64VecU8) -> StagedUpgradeJson {
+        let initial_storage_usage = env::storage_usage();
+        self.assert_owner();
+
+        let code: Vec<u8> = code.into();
+        assert!(!code.is_empty(), "Paras: code is empty");
+        let staged_at = to_sec(env::block_timestamp());
+        let staged_upgrade = StagedUpgrade {
+            code_hash: env::sha256(&code),
+            staged_at,
+            activates_at: staged_at + self.upgrade_delay_sec,
+        };
+        self.staged_code.set(&code);
+
+        NearEvent::log_stage_upgrade(
+            to_hex(&staged_upgrade.code_hash),
+            staged_upgrade.activates_at,
+        );
+        self.staged_upgrade = Some(staged_upgrade);
+
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage), 0);
+
+        self.get_staged_upgrade().unwrap()
+    }
+
+    #[payable]
+    pub fn cancel_upgrade(&mut self) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let staged_upgrade = self.staged_upgrade.take().expect("Paras: no staged upgrade");
+        self.remove_staged_code();
+
+        NearEvent::log_cancel_upgrade(to_hex(&staged_upgrade.code_hash));
+    }
+
+    /// deploys the staged code on this account and calls migrate on it
+    pub fn deploy_upgrade(&mut self) -> Promise {
+        self.assert_owner();
+
+        let staged_upgrade = self.staged_upgrade.take().expect("Paras: no staged upgrade");
+        assert!(
+            to_sec(env::block_timestamp()) >= staged_upgrade.activates_at,
+            "Paras: upgrade is timelocked until {}",
+            staged_upgrade.activates_at
+        );
+        let code = self.staged_code.get().expect("Paras: no staged code");
+        self.remove_staged_code();
+
+        NearEvent::log_deploy_upgrade(to_hex(&staged_upgrade.code_hash));
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(
+                b"migrate".to_vec(),
+                json!({}).to_string().into_bytes(),
+                NO_DEPOSIT,
+                GAS_FOR_UPGRADE_MIGRATE,
+            )
+    }
+
+    /// the owner paid the storage of the staged code in stage_upgrade, it is refunded once removed
+    fn remove_staged_code(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        self.staged_code.remove();
+        let freed_storage = initial_storage_usage.saturating_sub(env::storage_usage());
+        let owner_id = self.tokens.owner_id.clone();
+        self.refund_storage(&owner_id, env::storage_byte_cost() * Balance::from(freed_storage));
+    }
+
+    #[payable]
+    pub fn set_upgrade_delay(&mut self, upgrade_delay_sec: TimestampSec) {
+        assert_one_yocto();
+        self.assert_owner();
+        assert!(
+            upgrade_delay_sec >= MIN_UPGRADE_DELAY_SEC,
+            "Paras: upgrade delay is less than {}",
+            MIN_UPGRADE_DELAY_SEC
+        );
+
+        NearEvent::log_set_upgrade_delay(self.upgrade_delay_sec, upgrade_delay_sec);
+        self.upgrade_delay_sec = upgrade_delay_sec;
+    }
+
+    pub fn get_upgrade_delay(&self) -> TimestampSec {
+        self.upgrade_delay_sec
+    }
+
+    pub fn get_staged_upgrade(&self) -> Option<StagedUpgradeJson> {
+        self.staged_upgrade.as_ref().map(|staged_upgrade| StagedUpgradeJson {
+            code_hash: to_hex(&staged_upgrade.code_hash),
+            staged_at: staged_upgrade.staged_at,
+            activates_at: staged_upgrade.activates_at,
+        })
+    }
+
+    fn assert_owner(&self) {
         assert_eq!(
             env::predecessor_account_id(),
             self.tokens.owner_id,
             "Paras: Owner only"
         );
-        self.treasury_id = treasury_id.to_string();
+    }
+
+    fn is_owner_or_has_role(&self, role: Role, account_id: &AccountId) -> bool {
+        account_id == &self.tokens.owner_id
+            || self.role_members
+                .get(&role)
+                .map(|members| members.contains(account_id))
+                .unwrap_or(false)
+    }
+
+    fn assert_creator_or_delegated_minter(&self, token_series: &TokenSeries) {
+        let account_id = env::predecessor_account_id();
+        assert!(
+            account_id == token_series.creator_id
+                || (token_series.is_minter_delegated && self.is_owner_or_has_role(Role::Minter, &account_id)),
+            "Paras: not creator or delegated minter"
+        );
+    }
+
+    fn assert_owner_or_role(&self, role: Role) {
+        assert!(
+            self.is_owner_or_has_role(role, &env::predecessor_account_id()),
+            "Paras: Owner or {:?} only",
+            role
+        );
     }
 
     // CUSTOM
 
     #[payable]
+    #[allow(clippy::too_many_arguments)]
     pub fn nft_create_series(
         &mut self,
         token_metadata: TokenMetadata,
         price: Option<U128>,
         royalty: Option<HashMap<AccountId, u32>>,
+        ft_token_id: Option<ValidAccountId>,
+        sale_start: Option<TimestampSec>,
+        sale_end: Option<TimestampSec>,
+        max_per_account: Option<u32>,
+        primary_split: Option<HashMap<AccountId, u32>>,
+        transferable: Option<bool>,
     ) -> TokenSeriesJson {
+        self.assert_not_paused(PausableFeature::SeriesCreation);
         let initial_storage_usage = env::storage_usage();
         let caller_id = env::predecessor_account_id();
 
+        // validity window is copied to every token, only enforced when in Unix epoch milliseconds
+        let starts_at = parse_timestamp(&token_metadata.starts_at);
+        let expires_at = parse_timestamp(&token_metadata.expires_at);
+        if let Some(expires_at) = expires_at {
+            assert!(expires_at > env::block_timestamp(), "Paras: expires_at must be in the future");
+        }
+        if let (Some(starts_at), Some(expires_at)) = (starts_at, expires_at) {
+            assert!(starts_at < expires_at, "Paras: expires_at must be after starts_at");
+        }
+
         let token_series_id = format!("{}", (self.token_series_by_id.len() + 1));
 
         assert!(
@@ -323,28 +906,19 @@ impl Contract {
         assert!(title.is_some(), "Paras: token_metadata.title is required");
         
 
-        let mut total_perpetual = 0;
-        let mut total_accounts = 0;
-        let royalty_res: HashMap<AccountId, u32> = if let Some(royalty) = royalty {
-            for (k , v) in royalty.iter() {
-                if !is_valid_account_id(k.as_bytes()) {
-                    env::panic("Not valid account_id for royalty".as_bytes());
-                };
-                total_perpetual += *v;
-                total_accounts += 1;
-            }
-            royalty
-        } else {
-            HashMap::new()
-        };
-
-        assert!(total_accounts <= 50, "Paras: royalty exceeds 50 accounts");
+        let royalty_res: HashMap<AccountId, u32> = royalty.unwrap_or_default();
+        let total_perpetual = validate_payees(&royalty_res, "royalty");
 
         assert!(
             total_perpetual <= 9000,
             "Paras Exceeds maximum royalty -> 9000",
         );
 
+        let ft_token_id: Option<AccountId> = ft_token_id.map(|ft_token_id| ft_token_id.into());
+
+        let primary_split: HashMap<AccountId, u32> = primary_split.unwrap_or_default();
+        assert_valid_primary_split(&primary_split, &ft_token_id);
+
         let price_res: Option<u128> = if price.is_some() {
             assert!(
                 price.unwrap().0 < MAX_PRICE,
@@ -356,6 +930,12 @@ impl Contract {
             None
         };
 
+        if let Some(price) = price_res {
+            assert_valid_ft_price(&ft_token_id, price);
+        }
+
+        assert_valid_sale_window(sale_start, sale_end);
+
         self.token_series_by_id.insert(&token_series_id, &TokenSeries{
             metadata: token_metadata.clone(),
             creator_id: caller_id.to_string(),
@@ -369,37 +949,50 @@ impl Contract {
             price: price_res,
             is_mintable: true,
             royalty: royalty_res.clone(),
+            ft_token_id: ft_token_id.clone(),
+            sale_start,
+            sale_end,
+            presale_price: None,
+            allowlist: LookupMap::new(
+                StorageKey::SeriesAllowlist {
+                    token_series: token_series_id.clone(),
+                }
+                .try_to_vec()
+                .unwrap(),
+            ),
+            max_per_account,
+            dutch_auction: None,
+            primary_split: primary_split.clone(),
+            is_royalty_locked: false,
+            is_metadata_frozen: false,
+            transferable: transferable.unwrap_or(true),
+            transfer_lock_sec: None,
+            expiry_policy: ExpiryPolicy::Transferable,
+            is_minter_delegated: false,
         });
 
         // set market data transaction fee
         let current_transaction_fee = self.calculate_current_transaction_fee();
         self.market_data_transaction_fee.transaction_fee.insert(&token_series_id, &current_transaction_fee);
 
-        env::log(
-            json!({
-                "type": "nft_create_series",
-                "params": {
-                    "token_series_id": token_series_id,
-                    "token_metadata": token_metadata,
-                    "creator_id": caller_id,
-                    "price": price,
-                    "royalty": royalty_res,
-                    "transaction_fee": &current_transaction_fee.to_string()
-                }
-            })
-            .to_string()
-            .as_bytes(),
-        );
+        NearEvent::log_nft_create_series(NftCreateSeriesData {
+            token_series_id: token_series_id.clone(),
+            token_metadata,
+            creator_id: caller_id,
+            price: price_res.map(|price| price.to_string()),
+            royalty: royalty_res,
+            transaction_fee: current_transaction_fee.to_string(),
+            ft_token_id,
+            sale_start,
+            sale_end,
+            max_per_account,
+            primary_split,
+            transferable: transferable.unwrap_or(true),
+        });
 
-        refund_deposit(env::storage_usage() - initial_storage_usage, 0);
+        self.charge_storage(env::storage_usage() - initial_storage_usage, 0);
 
-		TokenSeriesJson{
-            token_series_id,
-			metadata: token_metadata,
-			creator_id: caller_id.into(),
-            royalty: royalty_res,
-            transaction_fee: current_transaction_fee.into()
-		}
+        self.nft_get_series_single(token_series_id)
     }
 
     #[payable]
@@ -409,54 +1002,300 @@ impl Contract {
         receiver_id: Option<ValidAccountId>
     ) -> TokenId {
         let initial_storage_usage = env::storage_usage();
-        let attached_deposit = env::attached_deposit();
-        let token_series = self.token_series_by_id.get(&token_series_id).expect("Paras: Token series not exist");
-        let price: u128 = token_series.price.expect("Paras: not for sale");
         let receiver_id: AccountId = if let Some(receiver_id) = receiver_id {
             receiver_id.to_string()
         } else {
             env::predecessor_account_id()
         };
-        assert!(
-            attached_deposit >= price,
-            "Paras: attached deposit is less than price : {}",
-            price
-        );
-        let token_id: TokenId = self._nft_mint_series(token_series_id.clone(), receiver_id.clone());
-
-        let for_treasury = price as u128 * self.calculate_new_market_data_transaction_fee(&token_series_id) / 10_000u128;
-        let price_deducted = price - for_treasury;
-        Promise::new(token_series.creator_id).transfer(price_deducted);
-
-        if for_treasury != 0 {
-            Promise::new(self.treasury_id.clone()).transfer(for_treasury);
-        }
+        let (token_ids, price) = self._nft_buy_series(&token_series_id, &receiver_id, 1);
 
-        refund_deposit(env::storage_usage() - initial_storage_usage, price);
+        self.charge_storage(env::storage_usage() - initial_storage_usage, price);
 
         NearEvent::log_nft_mint(
             receiver_id,
-            vec![token_id.clone()],
+            token_ids.clone(),
             Some(json!({"price": price.to_string()}).to_string())
         );
 
-        token_id
+        token_ids[0].clone()
     }
 
     #[payable]
-    pub fn nft_mint(
-        &mut self, 
+    pub fn nft_buy_batch(
+        &mut self,
         token_series_id: TokenSeriesId,
-        receiver_id: ValidAccountId
-    ) -> TokenId {
+        quantity: u64,
+        receiver_id: Option<ValidAccountId>
+    ) -> Vec<TokenId> {
         let initial_storage_usage = env::storage_usage();
-
-        let token_series = self.token_series_by_id.get(&token_series_id).expect("Paras: Token series not exist");
-        assert_eq!(env::predecessor_account_id(), token_series.creator_id, "Paras: not creator");
-        let token_id: TokenId = self._nft_mint_series(token_series_id, receiver_id.to_string());
+        let receiver_id: AccountId = if let Some(receiver_id) = receiver_id {
+            receiver_id.to_string()
+        } else {
+            env::predecessor_account_id()
+        };
+        let (token_ids, price) = self._nft_buy_series(&token_series_id, &receiver_id, quantity);
+
+        self.charge_storage(
+            env::storage_usage() - initial_storage_usage,
+            price * token_ids.len() as u128
+        );
+
+        NearEvent::log_nft_mint(
+            receiver_id,
+            token_ids.clone(),
+            Some(json!({"price": price.to_string()}).to_string())
+        );
+
+        token_ids
+    }
+
+    /// mint `quantity` editions paid with attached NEAR, returns the minted token ids and price per edition
+    fn _nft_buy_series(
+        &mut self,
+        token_series_id: &TokenSeriesId,
+        receiver_id: &AccountId,
+        quantity: u64
+    ) -> (Vec<TokenId>, Balance) {
+        assert_batch_size(quantity);
+        let attached_deposit = env::attached_deposit();
+        let mut token_series = self.token_series_by_id.get(token_series_id).expect("Paras: Token series not exist");
+        assert!(
+            token_series.ft_token_id.is_none(),
+            "Paras: series is priced in fungible token, use ft_transfer_call"
+        );
+        let price: u128 = self._nft_series_sale_price(
+            token_series_id,
+            &mut token_series,
+            &env::predecessor_account_id(),
+            quantity
+        );
+        let total_price = price * quantity as u128;
+        assert!(
+            attached_deposit >= total_price,
+            "Paras: attached deposit is less than price : {}",
+            total_price
+        );
+
+        let token_ids = self._nft_mint_series_batch(
+            token_series_id,
+            vec![receiver_id.clone(); quantity as usize],
+            &env::predecessor_account_id()
+        );
+
+        self._nft_distribute_sale(token_series_id, &token_series, total_price);
+
+        (token_ids, price)
+    }
+
+    /// price for `buyer_id`, uses the presale allocation before sale_start
+    /// and counts public sale purchases against max_per_account
+    fn _nft_series_sale_price(
+        &mut self,
+        token_series_id: &TokenSeriesId,
+        token_series: &mut TokenSeries,
+        buyer_id: &AccountId,
+        quantity: u64
+    ) -> Balance {
+        self.assert_not_paused(PausableFeature::PrimarySales);
+        assert!(
+            self.auction_by_series_id.get(token_series_id).is_none(),
+            "Paras: series is on auction"
+        );
+
+        if let (Some(sale_start), Some(presale_price)) = (token_series.sale_start, token_series.presale_price) {
+            if to_sec(env::block_timestamp()) < sale_start {
+                let allocation = token_series.allowlist.get(buyer_id).unwrap_or(0);
+                assert!(
+                    quantity <= allocation as u64,
+                    "Paras: presale allocation exceeded, remaining : {}",
+                    allocation
+                );
+                token_series.allowlist.insert(buyer_id, &(allocation - quantity as u32));
+                return presale_price;
+            }
+        }
+
+        assert_sale_open(token_series);
+        let price = series_current_price(token_series).expect("Paras: not for sale");
+
+        // only counted while a limit is set, buyers do not pay storage for it otherwise
+        if let Some(max_per_account) = token_series.max_per_account {
+            let mints_key = (token_series_id.clone(), buyer_id.clone());
+            let minted = self.series_mints_by_account.get(&mints_key).unwrap_or(0);
+            assert!(
+                minted as u64 + quantity <= max_per_account as u64,
+                "Paras: mint limit per account exceeded, remaining : {}",
+                max_per_account.saturating_sub(minted)
+            );
+            self.series_mints_by_account.insert(&mints_key, &(minted + quantity as u32));
+        }
+
+        price
+    }
+
+    #[payable]
+    pub fn nft_start_auction(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        reserve_price: U128,
+        end_time: TimestampSec
+    ) {
+        let initial_storage_usage = env::storage_usage();
+
+        let token_series = self.token_series_by_id.get(&token_series_id).expect("Paras: Token series not exist");
+        assert_eq!(env::predecessor_account_id(), token_series.creator_id, "Paras: Creator only");
+        assert!(token_series.is_mintable, "Paras: Token series is not mintable");
+        assert!(
+            token_series.ft_token_id.is_none(),
+            "Paras: auction is only available for series priced in NEAR"
+        );
+        assert!(
+            self.auction_by_series_id.get(&token_series_id).is_none(),
+            "Paras: series is on auction"
+        );
+        assert!(
+            reserve_price.0 < MAX_PRICE,
+            "Paras: price higher than {}",
+            MAX_PRICE
+        );
+        assert!(
+            reserve_price.0 >= AUCTION_MIN_RESERVE_PRICE,
+            "Paras: reserve_price is less than {}",
+            AUCTION_MIN_RESERVE_PRICE
+        );
+        assert!(
+            end_time > to_sec(env::block_timestamp()),
+            "Paras: end_time is less than current block_timestamp"
+        );
+
+        self.auction_by_series_id.insert(&token_series_id, &Auction {
+            reserve_price: reserve_price.0,
+            end_time,
+            highest_bid: None,
+        });
 
         refund_deposit(env::storage_usage() - initial_storage_usage, 0);
 
+        NearEvent::log_nft_start_auction(token_series_id, reserve_price.0.to_string(), end_time);
+    }
+
+    /// bid with the attached deposit, the previous highest bidder is refunded
+    #[payable]
+    pub fn nft_place_bid(&mut self, token_series_id: TokenSeriesId) {
+        self.assert_not_paused(PausableFeature::PrimarySales);
+        let mut auction = self.auction_by_series_id.get(&token_series_id).expect("Paras: no auction for series");
+        let bidder_id = env::predecessor_account_id();
+        let amount = env::attached_deposit();
+        let now = to_sec(env::block_timestamp());
+        assert!(now < auction.end_time, "Paras: auction has ended");
+
+        let min_bid = if let Some(highest_bid) = &auction.highest_bid {
+            // strictly higher even when the increment rounds down to 0
+            highest_bid.amount + std::cmp::max(highest_bid.amount * AUCTION_MIN_BID_INCREMENT / 10_000u128, 1)
+        } else {
+            auction.reserve_price
+        };
+        assert!(amount >= min_bid, "Paras: bid is less than {}", min_bid);
+
+        if let Some(highest_bid) = auction.highest_bid.take() {
+            Promise::new(highest_bid.bidder_id).transfer(highest_bid.amount);
+        }
+
+        // anti-sniping
+        if auction.end_time - now < AUCTION_EXTENSION_SEC {
+            auction.end_time = now + AUCTION_EXTENSION_SEC;
+        }
+
+        auction.highest_bid = Some(Bid {
+            bidder_id: bidder_id.clone(),
+            amount,
+        });
+        self.auction_by_series_id.insert(&token_series_id, &auction);
+
+        NearEvent::log_nft_place_bid(token_series_id, bidder_id, amount.to_string(), auction.end_time);
+    }
+
+    /// mint the next edition to the highest bidder once the auction ended, callable by anyone
+    pub fn nft_settle_auction(&mut self, token_series_id: TokenSeriesId) -> Option<TokenId> {
+        let auction = self.auction_by_series_id.get(&token_series_id).expect("Paras: no auction for series");
+        assert!(
+            to_sec(env::block_timestamp()) >= auction.end_time,
+            "Paras: auction has not ended"
+        );
+        let token_series = self.token_series_by_id.get(&token_series_id).expect("Paras: Token series not exist");
+
+        let initial_storage_usage = env::storage_usage();
+        self.auction_by_series_id.remove(&token_series_id);
+        // the creator paid for the record without a bid in nft_start_auction
+        let bid_storage = auction.highest_bid
+            .as_ref()
+            .map(|highest_bid| highest_bid.try_to_vec().unwrap().len() as StorageUsage)
+            .unwrap_or(0);
+        let freed_storage = initial_storage_usage.saturating_sub(env::storage_usage()).saturating_sub(bid_storage);
+        self.refund_storage(&token_series.creator_id, env::storage_byte_cost() * Balance::from(freed_storage));
+
+        let highest_bid = if let Some(highest_bid) = auction.highest_bid {
+            highest_bid
+        } else {
+            NearEvent::log_nft_settle_auction(token_series_id, None, None, None);
+            return None;
+        };
+
+        let has_copies_left = token_series.metadata.copies
+            .map(|copies| token_series.tokens.len() < copies)
+            .unwrap_or(true);
+        if !token_series.is_mintable || !has_copies_left {
+            // supply ran out during the auction
+            Promise::new(highest_bid.bidder_id.clone()).transfer(highest_bid.amount);
+            NearEvent::log_nft_settle_auction(token_series_id, None, None, None);
+            return None;
+        }
+
+        let initial_storage_usage = env::storage_usage();
+        let token_id: TokenId = self._nft_mint_series(
+            token_series_id.clone(),
+            highest_bid.bidder_id.clone(),
+            &token_series.creator_id
+        );
+
+        // storage for the minted token is paid from the proceeds, reserve_price covers it
+        let storage_cost = env::storage_byte_cost() * Balance::from(env::storage_usage() - initial_storage_usage);
+        self._nft_distribute_sale(&token_series_id, &token_series, highest_bid.amount.saturating_sub(storage_cost));
+
+        NearEvent::log_nft_mint(
+            highest_bid.bidder_id.clone(),
+            vec![token_id.clone()],
+            Some(json!({"price": highest_bid.amount.to_string()}).to_string())
+        );
+        NearEvent::log_nft_settle_auction(
+            token_series_id,
+            Some(highest_bid.bidder_id),
+            Some(highest_bid.amount.to_string()),
+            Some(token_id.clone()),
+        );
+
+        Some(token_id)
+    }
+
+    #[payable]
+    pub fn nft_mint(
+        &mut self, 
+        token_series_id: TokenSeriesId,
+        receiver_id: ValidAccountId
+    ) -> TokenId {
+        let initial_storage_usage = env::storage_usage();
+
+        self.assert_not_paused(PausableFeature::CreatorMints);
+        let token_series = self.token_series_by_id.get(&token_series_id).expect("Paras: Token series not exist");
+        self.assert_creator_or_delegated_minter(&token_series);
+        let token_id: TokenId = self._nft_mint_series(
+            token_series_id,
+            receiver_id.to_string(),
+            &env::predecessor_account_id()
+        );
+
+        self.charge_storage(env::storage_usage() - initial_storage_usage, 0);
+
         NearEvent::log_nft_mint(
             receiver_id.to_string(),
             vec![token_id.clone()],
@@ -466,6 +1305,39 @@ impl Contract {
         token_id
     }
 
+    #[payable]
+    pub fn nft_mint_batch(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        receiver_ids: Vec<ValidAccountId>
+    ) -> Vec<TokenId> {
+        let initial_storage_usage = env::storage_usage();
+
+        self.assert_not_paused(PausableFeature::CreatorMints);
+        let token_series = self.token_series_by_id.get(&token_series_id).expect("Paras: Token series not exist");
+        self.assert_creator_or_delegated_minter(&token_series);
+        let receiver_ids: Vec<AccountId> = receiver_ids.into_iter().map(|receiver_id| receiver_id.into()).collect();
+        let token_ids = self._nft_mint_series_batch(&token_series_id, receiver_ids.clone(), &env::predecessor_account_id());
+
+        self.charge_storage(env::storage_usage() - initial_storage_usage, 0);
+
+        // group token ids by owner so a single event covers the whole batch
+        let mut mint_data: Vec<NftMintData> = vec![];
+        for (receiver_id, token_id) in receiver_ids.into_iter().zip(token_ids.iter()) {
+            match mint_data.iter_mut().find(|data| data.owner_id == receiver_id) {
+                Some(data) => data.token_ids.push(token_id.clone()),
+                None => mint_data.push(NftMintData {
+                    owner_id: receiver_id,
+                    token_ids: vec![token_id.clone()],
+                    memo: None,
+                }),
+            }
+        }
+        NearEvent::log_nft_mints(mint_data);
+
+        token_ids
+    }
+
     #[payable]
     pub fn nft_mint_and_approve(
         &mut self, 
@@ -475,35 +1347,32 @@ impl Contract {
     ) -> Option<Promise> {
         let initial_storage_usage = env::storage_usage();
 
+        self.assert_not_paused(PausableFeature::CreatorMints);
         let token_series = self.token_series_by_id.get(&token_series_id).expect("Paras: Token series not exist");
         assert_eq!(env::predecessor_account_id(), token_series.creator_id, "Paras: not creator");
-        let token_id: TokenId = self._nft_mint_series(token_series_id, token_series.creator_id.clone());
-
-        // Need to copy the nft_approve code here to solve the gas problem
-        // get contract-level LookupMap of token_id to approvals HashMap
-        let approvals_by_id = self.tokens.approvals_by_id.as_mut().unwrap();
+        let token_id: TokenId = self._nft_mint_series(
+            token_series_id,
+            token_series.creator_id.clone(),
+            &token_series.creator_id
+        );
 
-        // update HashMap of approvals for this token
-        let approved_account_ids =
-            &mut approvals_by_id.get(&token_id).unwrap_or_else(|| HashMap::new());
+        // approve in the same call to save the gas of a second transaction
         let account_id: AccountId = account_id.into();
-        let approval_id: u64 =
-            self.tokens.next_approval_id_by_id.as_ref().unwrap().get(&token_id).unwrap_or_else(|| 1u64);
-        approved_account_ids.insert(account_id.clone(), approval_id);
-
-        // save updated approvals HashMap to contract's LookupMap
-        approvals_by_id.insert(&token_id, &approved_account_ids);
+        let approval_id = self.internal_approve(&token_id, &account_id);
 
-        // increment next_approval_id for this token
-        self.tokens.next_approval_id_by_id.as_mut().unwrap().insert(&token_id, &(approval_id + 1));
-
-        refund_deposit(env::storage_usage() - initial_storage_usage, 0);
+        self.charge_storage(env::storage_usage() - initial_storage_usage, 0);
 
         NearEvent::log_nft_mint(
             token_series.creator_id.clone(),
             vec![token_id.clone()],
             None,
         );
+        NearEvent::log_nft_approve(
+            token_id.clone(),
+            token_series.creator_id.clone(),
+            account_id.clone(),
+            approval_id,
+        );
 
         if let Some(msg) = msg {
             Some(ext_approval_receiver::nft_on_approve(
@@ -520,11 +1389,105 @@ impl Contract {
         }
     }
 
+    /// split sale proceeds between the series primary_split (or creator) and treasury, paid in the series currency
+    fn _nft_distribute_sale(
+        &mut self,
+        token_series_id: &TokenSeriesId,
+        token_series: &TokenSeries,
+        price: Balance
+    ) {
+        let for_treasury = price * self.calculate_new_market_data_transaction_fee(token_series_id) / 10_000u128;
+        let price_deducted = price - for_treasury;
+
+        let mut distributed: Balance = 0;
+        for (account_id, share) in token_series.primary_split.iter() {
+            let amount = royalty_to_payout(*share, price_deducted).0;
+            if amount != 0 {
+                transfer_proceeds(&token_series.ft_token_id, account_id.clone(), amount);
+                distributed += amount;
+            }
+        }
+
+        // everything when there is no primary_split, otherwise the rounding dust
+        let for_creator = price_deducted - distributed;
+        if for_creator != 0 {
+            transfer_proceeds(&token_series.ft_token_id, token_series.creator_id.clone(), for_creator);
+        }
+
+        if for_treasury != 0 {
+            transfer_proceeds(&token_series.ft_token_id, self.treasury_id.clone(), for_treasury);
+        }
+    }
+
+    /// records proceeds the fungible token contract failed to transfer, e.g. to an unregistered account
+    #[private]
+    pub fn ft_resolve_payout(&mut self, ft_token_id: AccountId, receiver_id: AccountId, amount: U128) {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            return;
+        }
+
+        let key = (ft_token_id.clone(), receiver_id.clone());
+        let unclaimed = self.unclaimed_ft_payouts.get(&key).unwrap_or(0);
+        self.unclaimed_ft_payouts.insert(&key, &(unclaimed + amount.0));
+
+        NearEvent::log_ft_payout_failed(ft_token_id, receiver_id, amount.0.to_string());
+    }
+
+    /// retry the transfer of proceeds that failed, once the account is registered on the fungible token
+    #[payable]
+    pub fn claim_ft_payout(&mut self, ft_token_id: ValidAccountId) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let amount = self
+            .unclaimed_ft_payouts
+            .remove(&(ft_token_id.to_string(), account_id.clone()))
+            .expect("Paras: no payout to claim");
+
+        transfer_proceeds(&Some(ft_token_id.to_string()), account_id.clone(), amount);
+
+        NearEvent::log_ft_payout_claimed(ft_token_id.to_string(), account_id, amount.to_string());
+    }
+
+    pub fn get_unclaimed_ft_payout(&self, ft_token_id: ValidAccountId, account_id: ValidAccountId) -> U128 {
+        self.unclaimed_ft_payouts
+            .get(&(ft_token_id.to_string(), account_id.to_string()))
+            .unwrap_or(0)
+            .into()
+    }
+
+    fn _nft_mint_series_batch(
+        &mut self,
+        token_series_id: &TokenSeriesId,
+        receiver_ids: Vec<AccountId>,
+        storage_payer_id: &AccountId
+    ) -> Vec<TokenId> {
+        assert!(!receiver_ids.is_empty(), "Paras: nothing to mint");
+        assert_batch_size(receiver_ids.len() as u64);
+
+        let token_series = self.token_series_by_id.get(token_series_id).expect("Paras: Token series not exist");
+        if let Some(copies) = token_series.metadata.copies {
+            let available_copies = copies - token_series.tokens.len();
+            assert!(
+                receiver_ids.len() as u64 <= available_copies,
+                "Paras: not enough copies left : {}",
+                available_copies
+            );
+        }
+
+        receiver_ids
+            .into_iter()
+            .map(|receiver_id| self._nft_mint_series(token_series_id.clone(), receiver_id, storage_payer_id))
+            .collect()
+    }
+
+    /// storage_payer_id gets the storage of the token back when it is burned
     fn _nft_mint_series(
         &mut self, 
         token_series_id: TokenSeriesId,
-        receiver_id: AccountId
+        receiver_id: AccountId,
+        storage_payer_id: &AccountId
     ) -> TokenId {
+        let initial_storage_usage = env::storage_usage();
         let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Paras: Token series not exist");
         assert!(
             token_series.is_mintable,
@@ -538,12 +1501,17 @@ impl Contract {
         if (num_tokens + 1) >= max_copies {
             token_series.is_mintable = false;
             token_series.price = None;
+            token_series.dutch_auction = None;
         }
 
         let token_id = format!("{}{}{}", &token_series_id, TOKEN_DELIMETER, num_tokens + 1);
         token_series.tokens.insert(&token_id);
         self.token_series_by_id.insert(&token_series_id, &token_series);
 
+        if let Some(transfer_lock_sec) = token_series.transfer_lock_sec {
+            self.transfer_locked_until.insert(&token_id, &to_sec(env::block_timestamp()).saturating_add(transfer_lock_sec));
+        }
+
         // you can add custom metadata to each token here
         let metadata = Some(TokenMetadata {
             title: None,          // ex. "Arch Nemesis: Mail Carrier" or "Parcel #5055"
@@ -552,8 +1520,8 @@ impl Contract {
             media_hash: None, // Base64-encoded sha256 hash of content referenced by the `media` field. Required if `media` is included.
             copies: None, // number of copies of this set of metadata in existence when token was minted.
             issued_at: Some(env::block_timestamp().to_string()), // ISO 8601 datetime when token was issued or minted
-            expires_at: None, // ISO 8601 datetime when token expires
-            starts_at: None, // ISO 8601 datetime when token starts being valid
+            expires_at: token_series.metadata.expires_at.clone(), // copied from the series, Unix epoch in milliseconds when token expires
+            starts_at: token_series.metadata.starts_at.clone(), // copied from the series, Unix epoch in milliseconds when token starts being valid
             updated_at: None, // ISO 8601 datetime when token was last updated
             extra: None, // anything extra the NFT wants to store on-chain. Can be stringified JSON.
             reference: None, // URL to an off-chain JSON file with more info.
@@ -582,6 +1550,10 @@ impl Contract {
              tokens_per_owner.insert(&owner_id, &token_ids);
          }
 
+        // written twice so the record counts itself, StorageUsage keeps its size
+        self.storage_paid_by_token_id.insert(&token_id, &(storage_payer_id.clone(), 0));
+        let storage_used = env::storage_usage() - initial_storage_usage;
+        self.storage_paid_by_token_id.insert(&token_id, &(storage_payer_id.clone(), storage_used));
 
         token_id
     }
@@ -614,18 +1586,9 @@ impl Contract {
         let is_non_mintable = if (copies - decrease_copies.0) == minted_copies {
             token_series.is_mintable = false;
             token_series.price = None;
+            token_series.dutch_auction = None;
 
-            env::log(
-                json!({
-                    "type": "nft_set_series_price",
-                    "params": {
-                        "token_series_id": token_series_id,
-                        "price": Null,
-                    }
-                })
-                .to_string()
-                .as_bytes(),
-            );
+            NearEvent::log_nft_set_series_price(token_series_id.clone(), None, None);
 
             true
         } else {
@@ -635,21 +1598,31 @@ impl Contract {
         token_series.metadata.copies = Some(copies - decrease_copies.0);
 
         self.token_series_by_id.insert(&token_series_id, &token_series);
-        env::log(
-            json!({
-                "type": "nft_decrease_series_copies",
-                "params": {
-                    "token_series_id": token_series_id,
-                    "copies": U64::from(token_series.metadata.copies.unwrap()),
-                    "is_non_mintable": is_non_mintable,
-                }
-            })
-            .to_string()
-            .as_bytes(),
+        NearEvent::log_nft_decrease_series_copies(
+            token_series_id.clone(),
+            token_series.metadata.copies.unwrap().to_string(),
+            is_non_mintable,
         );
+        log_series_metadata_update(&token_series_id, &token_series, 0);
         U64::from(token_series.metadata.copies.unwrap())
     }
 
+    /// log nft_metadata_update for the tokens of the series from from_index, for series with more
+    /// editions than a single call can log. Returns the number of tokens left to log
+    #[payable]
+    pub fn nft_log_series_metadata_update(&mut self, token_series_id: TokenSeriesId, from_index: U64) -> U64 {
+        assert_one_yocto();
+
+        let token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Paras: Creator only"
+        );
+
+        log_series_metadata_update(&token_series_id, &token_series, from_index.0).into()
+    }
+
     #[payable]
     pub fn nft_set_series_price(&mut self, token_series_id: TokenSeriesId, price: Option<U128>) -> Option<U128> {
         assert_one_yocto();
@@ -667,6 +1640,9 @@ impl Contract {
             "Paras: token series is not mintable"
         );
 
+        // a fixed price replaces the dutch auction curve
+        token_series.dutch_auction = None;
+
         if price.is_none() {
             token_series.price = None;
         } else {
@@ -675,6 +1651,7 @@ impl Contract {
                 "Paras: price higher than {}",
                 MAX_PRICE
             );
+            assert_valid_ft_price(&token_series.ft_token_id, price.unwrap().0);
             token_series.price = Some(price.unwrap().0);
         }
 
@@ -684,518 +1661,1679 @@ impl Contract {
         let current_transaction_fee = self.calculate_current_transaction_fee();
         self.market_data_transaction_fee.transaction_fee.insert(&token_series_id, &current_transaction_fee);
 
-        env::log(
-            json!({
-                "type": "nft_set_series_price",
-                "params": {
-                    "token_series_id": token_series_id,
-                    "price": price,
-                    "transaction_fee": current_transaction_fee.to_string()
-                }
-            })
-            .to_string()
-            .as_bytes(),
+        NearEvent::log_nft_set_series_price(
+            token_series_id,
+            price.map(|price| price.0.to_string()),
+            Some(current_transaction_fee.to_string()),
         );
         return price;
     }
 
     #[payable]
-    pub fn nft_burn(&mut self, token_id: TokenId) {
+    pub fn nft_set_series_dutch_auction(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        dutch_auction: DutchAuction
+    ) {
         assert_one_yocto();
 
-        let owner_id = self.tokens.owner_by_id.get(&token_id).unwrap();
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
         assert_eq!(
-            owner_id,
             env::predecessor_account_id(),
-            "Token owner only"
+            token_series.creator_id,
+            "Paras: Creator only"
         );
+        assert!(token_series.is_mintable, "Paras: token series is not mintable");
 
-        if let Some(next_approval_id_by_id) = &mut self.tokens.next_approval_id_by_id {
-            next_approval_id_by_id.remove(&token_id);
-        }
+        assert!(
+            dutch_auction.start_price.0 < MAX_PRICE,
+            "Paras: price higher than {}",
+            MAX_PRICE
+        );
+        assert!(
+            dutch_auction.floor_price.0 <= dutch_auction.start_price.0,
+            "Paras: floor_price is higher than start_price"
+        );
+        assert_valid_ft_price(&token_series.ft_token_id, dutch_auction.floor_price.0);
+        assert!(dutch_auction.decay_interval > 0, "Paras: decay_interval must be greater than 0");
 
-        if let Some(approvals_by_id) = &mut self.tokens.approvals_by_id {
-            approvals_by_id.remove(&token_id);
-        }
+        token_series.dutch_auction = Some(dutch_auction.clone());
+        self.token_series_by_id.insert(&token_series_id, &token_series);
 
-        if let Some(tokens_per_owner) = &mut self.tokens.tokens_per_owner {
-            let mut token_ids = tokens_per_owner.get(&owner_id).unwrap();
-            token_ids.remove(&token_id);
+        // set market data transaction fee
+        let current_transaction_fee = self.calculate_current_transaction_fee();
+        self.market_data_transaction_fee.transaction_fee.insert(&token_series_id, &current_transaction_fee);
 
-            // remove the owner if there are no more tokens
-            if token_ids.is_empty() {
-                tokens_per_owner.remove(&owner_id);
-            } else {
-                tokens_per_owner.insert(&owner_id, &token_ids);
-            }
-        }
+        NearEvent::log_nft_set_series_dutch_auction(NftSetSeriesDutchAuctionData {
+            token_series_id,
+            start_price: dutch_auction.start_price.0.to_string(),
+            floor_price: dutch_auction.floor_price.0.to_string(),
+            start_time: dutch_auction.start_time,
+            decay_interval: dutch_auction.decay_interval,
+            price_decrement: dutch_auction.price_decrement.0.to_string(),
+            transaction_fee: current_transaction_fee.to_string(),
+        });
+    }
 
-        if let Some(token_metadata_by_id) = &mut self.tokens.token_metadata_by_id {
-            token_metadata_by_id.remove(&token_id);
-        }
+    #[payable]
+    pub fn nft_set_series_royalty(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        royalty: HashMap<AccountId, u32>
+    ) {
+        assert!(
+            env::attached_deposit() >= 1,
+            "Paras: Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
 
-        self.tokens.owner_by_id.remove(&token_id);
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Paras: Creator only"
+        );
+        assert!(!token_series.is_royalty_locked, "Paras: royalty is locked");
 
-        NearEvent::log_nft_burn(
-            owner_id,
-            vec![token_id],
-            None,
-            None,
+        let total_perpetual = validate_payees(&royalty, "royalty");
+        assert!(
+            total_perpetual <= 9000,
+            "Paras Exceeds maximum royalty -> 9000",
         );
-    }
 
-    // CUSTOM VIEWS
-
-	pub fn nft_get_series_single(&self, token_series_id: TokenSeriesId) -> TokenSeriesJson {
-		let token_series = self.token_series_by_id.get(&token_series_id).expect("Series does not exist");
-        let current_transaction_fee = self.get_market_data_transaction_fee(&token_series_id);
-		TokenSeriesJson{
-            token_series_id,
-			metadata: token_series.metadata,
-			creator_id: token_series.creator_id,
-            royalty: token_series.royalty,
-            transaction_fee: current_transaction_fee.into() 
-		}
-	}
+        let old_royalty = std::mem::replace(&mut token_series.royalty, royalty.clone());
+        self.token_series_by_id.insert(&token_series_id, &token_series);
 
-    pub fn nft_get_series_format(self) -> (char, &'static str, &'static str) {
-        (TOKEN_DELIMETER, TITLE_DELIMETER, EDITION_DELIMETER)
-    }
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage), 0);
 
-    pub fn nft_get_series_price(self, token_series_id: TokenSeriesId) -> Option<U128> {
-        let price = self.token_series_by_id.get(&token_series_id).unwrap().price;
-        match price {
-            Some(p) => return Some(U128::from(p)),
-            None => return None
-        };
+        NearEvent::log_nft_set_series_royalty(token_series_id, old_royalty, royalty);
     }
 
-    pub fn nft_get_series(
-        &self,
-        from_index: Option<U128>,
-        limit: Option<u64>,
-    ) -> Vec<TokenSeriesJson> {
-        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
-        assert!(
-            (self.token_series_by_id.len() as u128) > start_index,
-            "Out of bounds, please use a smaller from_index."
-        );
-        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
-        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+    /// contract owner or admin locks the series royalty so it cannot be changed anymore
+    #[payable]
+    pub fn nft_lock_series_royalty(&mut self, token_series_id: TokenSeriesId) {
+        assert_one_yocto();
+        self.assert_owner_or_role(Role::Admin);
 
-        self.token_series_by_id
-            .iter()
-            .skip(start_index as usize)
-            .take(limit)
-            .map(|(token_series_id, token_series)| 
-                 {
-                    let current_transaction_fee = self.get_market_data_transaction_fee(&token_series_id);
-                    return TokenSeriesJson{
-                        token_series_id,
-                        metadata: token_series.metadata,
-                        creator_id: token_series.creator_id,
-                        royalty: token_series.royalty,
-                        transaction_fee: current_transaction_fee.into()
-                }
-            })
-            .collect()
-    }
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert!(!token_series.is_royalty_locked, "Paras: royalty is locked");
+        token_series.is_royalty_locked = true;
+        self.token_series_by_id.insert(&token_series_id, &token_series);
 
-    pub fn nft_supply_for_series(&self, token_series_id: TokenSeriesId) -> U64 {
-        self.token_series_by_id.get(&token_series_id).expect("Token series not exist").tokens.len().into()
+        NearEvent::log_nft_lock_series_royalty(token_series_id);
     }
 
-    pub fn nft_tokens_by_series(
-        &self,
+    /// update series metadata while no edition is minted, freeze_metadata makes it permanent
+    #[payable]
+    pub fn nft_update_series_metadata(
+        &mut self,
         token_series_id: TokenSeriesId,
-        from_index: Option<U128>,
-        limit: Option<u64>,
-    ) -> Vec<Token> {
-        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
-        let tokens = self.token_series_by_id.get(&token_series_id).unwrap().tokens;
+        metadata: Option<SeriesMetadataUpdate>,
+        freeze_metadata: Option<bool>,
+    ) -> TokenSeriesJson {
         assert!(
-            (tokens.len() as u128) > start_index,
-            "Out of bounds, please use a smaller from_index."
+            env::attached_deposit() >= 1,
+            "Paras: Requires attached deposit of at least 1 yoctoNEAR"
         );
-        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
-        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        let initial_storage_usage = env::storage_usage();
 
-        tokens
-            .iter()
-            .skip(start_index as usize)
-            .take(limit)
-            .map(|token_id| self.nft_token(token_id).unwrap())
-            .collect()
-    }
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Paras: Creator only"
+        );
+        assert!(!token_series.is_metadata_frozen, "Paras: metadata is frozen");
 
-    pub fn nft_token(&self, token_id: TokenId) -> Option<Token> {
-        let owner_id = self.tokens.owner_by_id.get(&token_id)?;
-        let approved_account_ids = self
-            .tokens
-            .approvals_by_id
-            .as_ref()
-            .and_then(|by_id| by_id.get(&token_id).or_else(|| Some(HashMap::new())));
+        if let Some(metadata) = metadata {
+            let minted_copies = token_series.tokens.len();
+            assert_eq!(
+                minted_copies, 0,
+                "Paras: cannot update metadata, already minted : {}", minted_copies
+            );
 
-        // CUSTOM (switch metadata for the token_series metadata)
-        let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
-        let token_series_id = token_id_iter.next().unwrap().parse().unwrap();
-                let series_metadata = self.token_series_by_id.get(&token_series_id).unwrap().metadata;
+            if let Some(title) = metadata.title {
+                token_series.metadata.title = Some(title);
+            }
+            if metadata.description.is_some() {
+                token_series.metadata.description = metadata.description;
+            }
+            if metadata.media.is_some() {
+                token_series.metadata.media = metadata.media;
+                token_series.metadata.media_hash = metadata.media_hash;
+            }
+            if metadata.extra.is_some() {
+                token_series.metadata.extra = metadata.extra;
+            }
+            if metadata.reference.is_some() {
+                token_series.metadata.reference = metadata.reference;
+                token_series.metadata.reference_hash = metadata.reference_hash;
+            }
+            token_series.metadata.updated_at = Some(env::block_timestamp().to_string());
+        }
 
-        let mut token_metadata = self.tokens.token_metadata_by_id.as_ref().unwrap().get(&token_id).unwrap();
+        if freeze_metadata.unwrap_or(false) {
+            token_series.is_metadata_frozen = true;
+        }
 
-        token_metadata.title = Some(format!(
-            "{}{}{}",
-            series_metadata.title.unwrap(),
-            TITLE_DELIMETER,
-            token_id_iter.next().unwrap()
-        ));
+        self.token_series_by_id.insert(&token_series_id, &token_series);
 
-        token_metadata.reference = series_metadata.reference;
-        token_metadata.media = series_metadata.media;
-        token_metadata.copies = series_metadata.copies;
-        token_metadata.extra = series_metadata.extra;
+        self.charge_storage_delta(initial_storage_usage);
 
-        Some(Token {
-            token_id,
-            owner_id,
-            metadata: Some(token_metadata),
-            approved_account_ids,
-        })
+        NearEvent::log_nft_update_series_metadata(
+            token_series_id.clone(),
+            token_series.metadata.clone(),
+            token_series.is_metadata_frozen,
+        );
+        log_series_metadata_update(&token_series_id, &token_series, 0);
+
+        self.token_series_to_json(token_series_id, token_series)
     }
 
     #[payable]
-    pub fn nft_transfer(
+    pub fn nft_set_series_sale_window(
         &mut self,
-        receiver_id: ValidAccountId,
-        token_id: TokenId,
-        approval_id: Option<u64>,
-        memo: Option<String>,
+        token_series_id: TokenSeriesId,
+        sale_start: Option<TimestampSec>,
+        sale_end: Option<TimestampSec>
     ) {
-        let sender_id = env::predecessor_account_id();
-        let previous_owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
-        let receiver_id_str = receiver_id.to_string();
-        self.tokens.nft_transfer(receiver_id, token_id.clone(), approval_id, memo.clone());
-
-        let authorized_id : Option<AccountId> = if sender_id != previous_owner_id {
-            Some(sender_id)
-        } else {
-             None
-        };
+        assert_one_yocto();
 
-        NearEvent::log_nft_transfer(
-            previous_owner_id,
-            receiver_id_str,
-            vec![token_id],
-            memo,
-             authorized_id,
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Paras: Creator only"
         );
+
+        assert_valid_sale_window(sale_start, sale_end);
+
+        token_series.sale_start = sale_start;
+        token_series.sale_end = sale_end;
+        self.token_series_by_id.insert(&token_series_id, &token_series);
+
+        NearEvent::log_nft_set_series_sale_window(token_series_id, sale_start, sale_end);
     }
 
     #[payable]
-    pub fn nft_transfer_call(
+    pub fn nft_set_series_max_per_account(
         &mut self,
-        receiver_id: ValidAccountId,
-        token_id: TokenId,
-        approval_id: Option<u64>,
-        memo: Option<String>,
-        msg: String,
-    ) -> PromiseOrValue<bool> {
+        token_series_id: TokenSeriesId,
+        max_per_account: Option<u32>
+    ) {
         assert_one_yocto();
-        let sender_id = env::predecessor_account_id();
-        let (previous_owner_id, old_approvals) = self.tokens.internal_transfer(
-            &sender_id,
-            receiver_id.as_ref(),
-            &token_id,
-            approval_id,
-            memo.clone(),
+
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Paras: Creator only"
         );
 
-        let authorized_id : Option<AccountId> = if sender_id != previous_owner_id {
-            Some(sender_id.clone())
-        } else {
-            None
-        };
+        token_series.max_per_account = max_per_account;
+        self.token_series_by_id.insert(&token_series_id, &token_series);
 
-        NearEvent::log_nft_transfer(
-            previous_owner_id.clone(),
-            receiver_id.to_string(),
-            vec![token_id.clone()],
-            memo,
-            authorized_id,
+        NearEvent::log_nft_set_series_max_per_account(token_series_id, max_per_account);
+    }
+
+    /// tokens minted after this call cannot be transferred until transfer_lock_sec after they were issued,
+    /// tokens already minted keep their lock
+    #[payable]
+    pub fn nft_set_series_transfer_lock(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        transfer_lock_sec: Option<TimestampSec>
+    ) {
+        assert_one_yocto();
+
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Paras: Creator only"
         );
+        assert!(token_series.transferable, "Paras: token is non-transferable");
+        if let Some(transfer_lock_sec) = transfer_lock_sec {
+            assert!(
+                transfer_lock_sec <= MAX_TRANSFER_LOCK_SEC,
+                "Paras: transfer_lock_sec is more than {}",
+                MAX_TRANSFER_LOCK_SEC
+            );
+        }
 
-        // Initiating receiver's call and the callback
-        ext_non_fungible_token_receiver::nft_on_transfer(
-            sender_id,
-            previous_owner_id.clone(),
-            token_id.clone(),
-            msg,
-            receiver_id.as_ref(),
-            NO_DEPOSIT,
-            env::prepaid_gas() - GAS_FOR_NFT_TRANSFER_CALL,
-        )
-        .then(ext_self::nft_resolve_transfer(
-            previous_owner_id,
-            receiver_id.into(),
-            token_id,
-            old_approvals,
-            &env::current_account_id(),
-            NO_DEPOSIT,
-            GAS_FOR_RESOLVE_TRANSFER,
-        ))
-        .into()
+        token_series.transfer_lock_sec = transfer_lock_sec;
+        self.token_series_by_id.insert(&token_series_id, &token_series);
+
+        NearEvent::log_nft_set_series_transfer_lock(token_series_id, transfer_lock_sec);
     }
 
-    // CUSTOM enumeration standard modified here because no macro below
+    #[payable]
+    pub fn nft_set_series_expiry_policy(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        expiry_policy: ExpiryPolicy
+    ) {
+        assert_one_yocto();
 
-    pub fn nft_total_supply(&self) -> U128 {
-        (self.tokens.owner_by_id.len() as u128).into()
-    }
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Paras: Creator only"
+        );
 
-    pub fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
-        // Get starting index, whether or not it was explicitly given.
-        // Defaults to 0 based on the spec:
-        // https://nomicon.io/Standards/NonFungibleToken/Enumeration.html#interface
-        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        // holders bought under the previous policy
         assert!(
-            (self.tokens.owner_by_id.len() as u128) > start_index,
-            "Out of bounds, please use a smaller from_index."
+            token_series.tokens.is_empty(),
+            "Paras: cannot change expiry policy, already minted : {}",
+            token_series.tokens.len()
         );
-        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
-        assert_ne!(limit, 0, "Cannot provide limit of 0.");
-        self.tokens
-            .owner_by_id
-            .iter()
-            .skip(start_index as usize)
-            .take(limit)
-            .map(|(token_id, _)| self.nft_token(token_id).unwrap())
-            .collect()
-    }
+        if expiry_policy != ExpiryPolicy::Transferable {
+            assert!(
+                parse_timestamp(&token_series.metadata.expires_at).is_some(),
+                "Paras: series has no expires_at in milliseconds"
+            );
+        }
 
-    pub fn nft_supply_for_owner(self, account_id: ValidAccountId) -> U128 {
-        let tokens_per_owner = self.tokens.tokens_per_owner.expect(
-            "Could not find tokens_per_owner when calling a method on the enumeration standard.",
-        );
-        tokens_per_owner
-            .get(account_id.as_ref())
-            .map(|account_tokens| U128::from(account_tokens.len() as u128))
-            .unwrap_or(U128(0))
+        token_series.expiry_policy = expiry_policy;
+        self.token_series_by_id.insert(&token_series_id, &token_series);
+
+        NearEvent::log_nft_set_series_expiry_policy(token_series_id, expiry_policy.as_str().to_string());
     }
 
-    pub fn nft_tokens_for_owner(
-        &self,
-        account_id: ValidAccountId,
-        from_index: Option<U128>,
-        limit: Option<u64>,
-    ) -> Vec<Token> {
-        let tokens_per_owner = self.tokens.tokens_per_owner.as_ref().expect(
-            "Could not find tokens_per_owner when calling a method on the enumeration standard.",
-        );
-        let token_set = if let Some(token_set) = tokens_per_owner.get(account_id.as_ref()) {
-            token_set
-        } else {
-            return vec![];
-        };
-        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
-        assert_ne!(limit, 0, "Cannot provide limit of 0.");
-        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
-        assert!(
-            token_set.len() as u128 > start_index,
-            "Out of bounds, please use a smaller from_index."
+    /// lets accounts with the minter role mint editions of the series, storage is paid by the minter
+    #[payable]
+    pub fn nft_set_series_minter_delegation(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        is_minter_delegated: bool
+    ) {
+        assert_one_yocto();
+
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Paras: Creator only"
         );
-        token_set
-            .iter()
-            .skip(start_index as usize)
-            .take(limit)
-            .map(|token_id| self.nft_token(token_id).unwrap())
-            .collect()
+
+        token_series.is_minter_delegated = is_minter_delegated;
+        self.token_series_by_id.insert(&token_series_id, &token_series);
+
+        NearEvent::log_nft_set_series_minter_delegation(token_series_id, is_minter_delegated);
     }
 
-    pub fn nft_payout(
-        &self, 
-        token_id: TokenId,
-        balance: U128, 
-        max_len_payout: u32
-    ) -> Payout{
-        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("No token id");
-        let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
-        let token_series_id = token_id_iter.next().unwrap().parse().unwrap();
-        let royalty = self.token_series_by_id.get(&token_series_id).expect("no type").royalty;
+    #[payable]
+    pub fn nft_set_series_presale_price(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        presale_price: Option<U128>
+    ) -> Option<U128> {
+        assert_one_yocto();
+
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Paras: Creator only"
+        );
+
+        if let Some(presale_price) = presale_price {
+            assert!(
+                presale_price.0 < MAX_PRICE,
+                "Paras: price higher than {}",
+                MAX_PRICE
+            );
+            assert_valid_ft_price(&token_series.ft_token_id, presale_price.0);
+        }
+        token_series.presale_price = presale_price.map(|presale_price| presale_price.0);
+        self.token_series_by_id.insert(&token_series_id, &token_series);
 
-        assert!(royalty.len() as u32 <= max_len_payout, "Market cannot payout to that many receivers");
+        NearEvent::log_nft_set_series_presale_price(
+            token_series_id,
+            presale_price.map(|presale_price| presale_price.0.to_string()),
+        );
+        presale_price
+    }
 
-        let balance_u128: u128 = balance.into();
+    /// add or overwrite allowlist entries, value is the max number of editions the account can buy in presale
+    #[payable]
+    pub fn nft_add_series_allowlist(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        allowlist: HashMap<AccountId, u32>
+    ) {
+        let initial_storage_usage = env::storage_usage();
 
-        let mut payout: Payout = Payout { payout: HashMap::new() };
-        let mut total_perpetual = 0;
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Paras: Creator only"
+        );
 
-        for (k, v) in royalty.iter() {
-            if *k != owner_id {
-                let key = k.clone();
-                payout.payout.insert(key, royalty_to_payout(*v, balance_u128));
-                total_perpetual += *v;
-            }
+        for (account_id, allocation) in allowlist.iter() {
+            if !is_valid_account_id(account_id.as_bytes()) {
+                env::panic("Not valid account_id for allowlist".as_bytes());
+            };
+            token_series.allowlist.insert(account_id, allocation);
         }
-        payout.payout.insert(owner_id, royalty_to_payout(10000 - total_perpetual, balance_u128));
-        payout
+
+        NearEvent::log_nft_add_series_allowlist(token_series_id, allowlist);
+
+        refund_deposit(env::storage_usage() - initial_storage_usage, 0);
     }
 
     #[payable]
-    pub fn nft_transfer_payout(
-        &mut self, 
-        receiver_id: ValidAccountId,
-        token_id: TokenId,
-        approval_id: Option<u64>,
-        balance: Option<U128>,
-        max_len_payout: Option<u32>
-    ) -> Option<Payout> {
+    pub fn nft_remove_series_allowlist(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        account_ids: Vec<AccountId>
+    ) {
         assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
 
-        let sender_id = env::predecessor_account_id();
-        // Transfer
-        let previous_token = self.nft_token(token_id.clone()).expect("no token");
-        self.tokens.nft_transfer(receiver_id.clone(), token_id.clone(), approval_id, None);
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        assert_eq!(
+            env::predecessor_account_id(),
+            token_series.creator_id,
+            "Paras: Creator only"
+        );
 
-        // Payout calculation
-        let previous_owner_id = previous_token.owner_id;
-        let mut total_perpetual = 0;
-        let payout = if let Some(balance) = balance {
-            let balance_u128: u128 = u128::from(balance);
-            let mut payout: Payout = Payout { payout: HashMap::new() };
-
-            let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
-            let token_series_id = token_id_iter.next().unwrap().parse().unwrap();
-            let royalty = self.token_series_by_id.get(&token_series_id).expect("no type").royalty;
-
-            assert!(royalty.len() as u32 <= max_len_payout.unwrap(), "Market cannot payout to that many receivers");
-            for (k, v) in royalty.iter() {
-                let key = k.clone();
-                if key != previous_owner_id {
-                    payout.payout.insert(key, royalty_to_payout(*v, balance_u128));
-                    total_perpetual += *v;
+        for account_id in account_ids.iter() {
+            token_series.allowlist.remove(account_id);
+        }
+
+        // the creator paid for the entries in nft_add_series_allowlist
+        let freed_storage = initial_storage_usage.saturating_sub(env::storage_usage());
+        self.refund_storage(&token_series.creator_id, env::storage_byte_cost() * Balance::from(freed_storage));
+
+        NearEvent::log_nft_remove_series_allowlist(token_series_id, account_ids);
+    }
+
+    /// approve account_id to transfer every token of the predecessor, or only the tokens of series_filter
+    #[payable]
+    pub fn nft_approve_all(&mut self, account_id: ValidAccountId, series_filter: Option<TokenSeriesId>) {
+        assert!(
+            env::attached_deposit() >= 1,
+            "Paras: Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+
+        let owner_id = env::predecessor_account_id();
+        let account_id: AccountId = account_id.into();
+        assert_ne!(owner_id, account_id, "Paras: cannot approve the owner");
+
+        let mut operators = self.operator_approvals.get(&owner_id).unwrap_or_default();
+        match series_filter.clone() {
+            None => {
+                operators.insert(account_id.clone(), None);
+            }
+            Some(token_series_id) => {
+                assert!(
+                    self.token_series_by_id.get(&token_series_id).is_some(),
+                    "Paras: Token series not exist"
+                );
+                // an operator approved for every token stays approved for every token
+                if let Some(token_series_ids) = operators.entry(account_id.clone()).or_insert_with(|| Some(vec![])) {
+                    if !token_series_ids.contains(&token_series_id) {
+                        token_series_ids.push(token_series_id);
+                    }
                 }
             }
+        }
+        self.operator_approvals.insert(&owner_id, &operators);
 
-            assert!(
-                total_perpetual <= 10000,
-                "Total payout overflow"
-            );
+        self.charge_storage_delta(initial_storage_usage);
 
-            payout.payout.insert(previous_owner_id.clone(), royalty_to_payout(10000 - total_perpetual, balance_u128));
-            Some(payout)
-        } else {
-            None
-        };
+        NearEvent::log_nft_approve_all(owner_id, account_id, series_filter);
+    }
 
-        let authorized_id : Option<AccountId> = if sender_id != previous_owner_id {
-            Some(sender_id)
+    /// revoke an operator, only for series_filter when set
+    #[payable]
+    pub fn nft_revoke_operator(&mut self, account_id: ValidAccountId, series_filter: Option<TokenSeriesId>) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+
+        let owner_id = env::predecessor_account_id();
+        let account_id: AccountId = account_id.into();
+        let mut operators = self.operator_approvals.get(&owner_id).expect("Paras: not an operator");
+        let token_series_ids = operators.remove(&account_id).expect("Paras: not an operator");
+
+        if let Some(token_series_id) = &series_filter {
+            let mut token_series_ids = token_series_ids
+                .expect("Paras: operator is approved for every series, revoke without series_filter");
+            token_series_ids.retain(|id| id != token_series_id);
+            if !token_series_ids.is_empty() {
+                operators.insert(account_id.clone(), Some(token_series_ids));
+            }
+        }
+
+        if operators.is_empty() {
+            self.operator_approvals.remove(&owner_id);
         } else {
-            None
-        };
+            self.operator_approvals.insert(&owner_id, &operators);
+        }
 
-        NearEvent::log_nft_transfer(
-            previous_owner_id,
-            receiver_id.to_string(),
-            vec![token_id],
-            None,
-            authorized_id,
-        );
+        let freed_storage = initial_storage_usage.saturating_sub(env::storage_usage());
+        self.refund_storage(&owner_id, env::storage_byte_cost() * Balance::from(freed_storage));
 
-        payout
+        NearEvent::log_nft_revoke_operator(owner_id, account_id, series_filter);
     }
 
-    pub fn get_owner(&self) -> AccountId {
-        self.tokens.owner_id.clone()
-    }
-}
+    #[payable]
+    pub fn nft_burn(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        self.assert_not_paused(PausableFeature::Burns);
 
-fn royalty_to_payout(a: u32, b: Balance) -> U128 {
-    U128(a as u128 * b / 10_000u128)
-}
+        let owner_id = self.tokens.owner_by_id.get(&token_id).unwrap();
+        assert_eq!(
+            owner_id,
+            env::predecessor_account_id(),
+            "Token owner only"
+        );
 
-// near_contract_standards::impl_non_fungible_token_core!(Contract, tokens);
-// near_contract_standards::impl_non_fungible_token_enumeration!(Contract, tokens);
-near_contract_standards::impl_non_fungible_token_approval!(Contract, tokens);
+        let storage_refund = self.internal_burn(&token_id, &owner_id);
 
-#[near_bindgen]
-impl NonFungibleTokenMetadataProvider for Contract {
-    fn nft_metadata(&self) -> NFTContractMetadata {
-        self.metadata.get().unwrap()
+        NearEvent::log_nft_burn(
+            owner_id,
+            vec![token_id],
+            Some(json!({"storage_refund": storage_refund.to_string()}).to_string()),
+            None,
+        );
     }
-}
 
-#[near_bindgen]
-impl NonFungibleTokenResolver for Contract {
-    #[private]
-    fn nft_resolve_transfer(
-        &mut self,
-        previous_owner_id: AccountId,
-        receiver_id: AccountId,
-        token_id: TokenId,
-        approved_account_ids: Option<HashMap<AccountId, u64>>,
-    ) -> bool {
-        let resp: bool = self.tokens.nft_resolve_transfer(
-            previous_owner_id.clone(),
-            receiver_id.clone(),
-            token_id.clone(),
-            approved_account_ids,
-        );
+    /// burn expired tokens of a series with the creator_burnable expiry policy
+    #[payable]
+    pub fn nft_burn_expired(&mut self, token_ids: Vec<TokenId>) {
+        assert_one_yocto();
+        self.assert_not_paused(PausableFeature::Burns);
+        assert!(!token_ids.is_empty(), "Paras: nothing to burn");
 
-        // if not successful, return nft back to original owner
-        if !resp {
-            NearEvent::log_nft_transfer(
-                receiver_id,
-                previous_owner_id,
-                vec![token_id],
-                None,
-                None,
+        let caller_id = env::predecessor_account_id();
+        let mut burns: Vec<NftBurnData> = vec![];
+        for token_id in token_ids {
+            let token_series_id = token_id.split(TOKEN_DELIMETER).next().unwrap().to_string();
+            let token_series = self.token_series_by_id.get(&token_series_id).expect("Paras: Token series not exist");
+            assert_eq!(caller_id, token_series.creator_id, "Paras: Creator only");
+            assert_eq!(
+                token_series.expiry_policy,
+                ExpiryPolicy::CreatorBurnable,
+                "Paras: expired tokens are not burnable"
             );
+            assert!(self.is_expired(&token_id), "Paras: token is not expired : {}", token_id);
+
+            let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+            let storage_refund = self.internal_burn(&token_id, &owner_id);
+            burns.push(NftBurnData {
+                authorized_id: Some(caller_id.clone()),
+                owner_id,
+                token_ids: vec![token_id],
+                memo: Some(json!({"storage_refund": storage_refund.to_string()}).to_string()),
+            });
         }
 
-        resp
+        NearEvent::log_nft_burns(burns);
     }
-}
 
-/// from https://github.com/near/near-sdk-rs/blob/e4abb739ff953b06d718037aa1b8ab768db17348/near-contract-standards/src/non_fungible_token/utils.rs#L29
+    /// remove token_id, approval storage goes back to owner_id and the storage paid at mint
+    /// to whoever paid it. Returns the refunded amount
+    fn internal_burn(&mut self, token_id: &TokenId, owner_id: &AccountId) -> Balance {
+        let initial_storage_usage = env::storage_usage();
 
-fn refund_deposit(storage_used: u64, extra_spend: Balance) {
-    let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
-    let attached_deposit = env::attached_deposit() - extra_spend;
+        if let Some(next_approval_id_by_id) = &mut self.tokens.next_approval_id_by_id {
+            next_approval_id_by_id.remove(token_id);
+        }
 
-    assert!(
-        required_cost <= attached_deposit,
-        "Must attach {} yoctoNEAR to cover storage",
-        required_cost,
-    );
+        if let Some(approvals_by_id) = &mut self.tokens.approvals_by_id {
+            approvals_by_id.remove(token_id);
+        }
 
-    let refund = attached_deposit - required_cost;
-    if refund > 1 {
-        Promise::new(env::predecessor_account_id()).transfer(refund);
-    }
-}
+        let approvals_refund = env::storage_byte_cost()
+            * Balance::from(initial_storage_usage.saturating_sub(env::storage_usage()));
+        self.refund_storage(owner_id, approvals_refund);
 
-fn to_sec(timestamp: Timestamp) -> TimestampSec {
-    (timestamp / 10u64.pow(9)) as u32
-}
+        let initial_storage_usage = env::storage_usage();
 
-#[cfg(all(test, not(target_arch = "wasm32")))]
-mod tests {
-    use super::*;
-    use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::MockedBlockchain;
-    use near_sdk::{testing_env};
+        if let Some(tokens_per_owner) = &mut self.tokens.tokens_per_owner {
+            let mut token_ids = tokens_per_owner.get(owner_id).unwrap();
+            token_ids.remove(token_id);
 
-    const STORAGE_FOR_CREATE_SERIES: Balance = 8540000000000000000000;
-    const STORAGE_FOR_MINT: Balance = 11280000000000000000000;
+            // remove the owner if there are no more tokens
+            if token_ids.is_empty() {
+                tokens_per_owner.remove(owner_id);
+            } else {
+                tokens_per_owner.insert(owner_id, &token_ids);
+            }
+        }
 
-    fn get_context(predecessor_account_id: ValidAccountId) -> VMContextBuilder {
-        let mut builder = VMContextBuilder::new();
-        builder
-            .current_account_id(accounts(0))
-            .signer_account_id(predecessor_account_id.clone())
-            .predecessor_account_id(predecessor_account_id);
-        builder
-    }
+        if let Some(token_metadata_by_id) = &mut self.tokens.token_metadata_by_id {
+            token_metadata_by_id.remove(token_id);
+        }
+
+        self.tokens.owner_by_id.remove(token_id);
+        self.transfer_locked_until.remove(token_id);
+        let storage_paid = self.storage_paid_by_token_id.remove(token_id);
+
+        // tokens minted before the payer was recorded, or paid by the contract, have no refund
+        let storage_refund = if let Some((storage_payer_id, storage_used)) = storage_paid {
+            let storage_freed = initial_storage_usage.saturating_sub(env::storage_usage());
+            let storage_refund = env::storage_byte_cost() * Balance::from(std::cmp::min(storage_freed, storage_used));
+            self.refund_storage(&storage_payer_id, storage_refund);
+            storage_refund
+        } else {
+            0
+        };
+
+        approvals_refund + storage_refund
+    }
+
+    // CUSTOM VIEWS
+
+	pub fn nft_get_series_single(&self, token_series_id: TokenSeriesId) -> TokenSeriesJson {
+		let token_series = self.token_series_by_id.get(&token_series_id).expect("Series does not exist");
+        self.token_series_to_json(token_series_id, token_series)
+	}
+
+    fn token_series_to_json(&self, token_series_id: TokenSeriesId, token_series: TokenSeries) -> TokenSeriesJson {
+        let current_transaction_fee = self.get_market_data_transaction_fee(&token_series_id);
+        TokenSeriesJson{
+            token_series_id,
+            metadata: token_series.metadata,
+            creator_id: token_series.creator_id,
+            royalty: token_series.royalty,
+            transaction_fee: current_transaction_fee.into(),
+            ft_token_id: token_series.ft_token_id,
+            sale_start: token_series.sale_start,
+            sale_end: token_series.sale_end,
+            presale_price: token_series.presale_price.map(U128::from),
+            max_per_account: token_series.max_per_account,
+            dutch_auction: token_series.dutch_auction,
+            primary_split: token_series.primary_split,
+            is_royalty_locked: token_series.is_royalty_locked,
+            is_metadata_frozen: token_series.is_metadata_frozen,
+            transferable: token_series.transferable,
+            transfer_lock_sec: token_series.transfer_lock_sec,
+            expiry_policy: token_series.expiry_policy,
+            is_minter_delegated: token_series.is_minter_delegated,
+        }
+    }
+
+    pub fn nft_get_series_format(self) -> (char, &'static str, &'static str) {
+        (TOKEN_DELIMETER, TITLE_DELIMETER, EDITION_DELIMETER)
+    }
+
+    pub fn nft_get_series_price(self, token_series_id: TokenSeriesId) -> Option<U128> {
+        let price = series_current_price(&self.token_series_by_id.get(&token_series_id).unwrap());
+        match price {
+            Some(p) => return Some(U128::from(p)),
+            None => return None
+        };
+    }
+
+    /// live price of the series, follows the dutch auction curve if set
+    pub fn nft_get_series_current_price(&self, token_series_id: TokenSeriesId) -> Option<U128> {
+        let token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        series_current_price(&token_series).map(U128::from)
+    }
+
+    pub fn nft_get_series(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<TokenSeriesJson> {
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        assert!(
+            (self.token_series_by_id.len() as u128) > start_index,
+            "Out of bounds, please use a smaller from_index."
+        );
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+
+        self.token_series_by_id
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|(token_series_id, token_series)| self.token_series_to_json(token_series_id, token_series))
+            .collect()
+    }
+
+    /// remaining presale allocation of account_id
+    pub fn nft_get_series_allowlist_status(&self, token_series_id: TokenSeriesId, account_id: ValidAccountId) -> u32 {
+        let token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        token_series.allowlist.get(account_id.as_ref()).unwrap_or(0)
+    }
+
+    /// editions account_id can still buy on public sale, None if the series has no limit
+    pub fn nft_get_series_remaining_for_account(
+        &self,
+        token_series_id: TokenSeriesId,
+        account_id: ValidAccountId
+    ) -> Option<u32> {
+        let token_series = self.token_series_by_id.get(&token_series_id).expect("Token series not exist");
+        let minted = self.series_mints_by_account
+            .get(&(token_series_id, account_id.to_string()))
+            .unwrap_or(0);
+        token_series.max_per_account.map(|max_per_account| max_per_account.saturating_sub(minted))
+    }
+
+    pub fn nft_get_series_auction(&self, token_series_id: TokenSeriesId) -> Option<AuctionJson> {
+        self.auction_by_series_id.get(&token_series_id).map(|auction| AuctionJson {
+            token_series_id,
+            reserve_price: auction.reserve_price.into(),
+            end_time: auction.end_time,
+            highest_bidder_id: auction.highest_bid.as_ref().map(|bid| bid.bidder_id.clone()),
+            highest_bid: auction.highest_bid.as_ref().map(|bid| bid.amount.into()),
+        })
+    }
+
+    pub fn nft_supply_for_series(&self, token_series_id: TokenSeriesId) -> U64 {
+        self.token_series_by_id.get(&token_series_id).expect("Token series not exist").tokens.len().into()
+    }
+
+    pub fn nft_tokens_by_series(
+        &self,
+        token_series_id: TokenSeriesId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token> {
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let tokens = self.token_series_by_id.get(&token_series_id).unwrap().tokens;
+        assert!(
+            (tokens.len() as u128) > start_index,
+            "Out of bounds, please use a smaller from_index."
+        );
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+
+        tokens
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|token_id| self.nft_token(token_id).unwrap())
+            .collect()
+    }
+
+    /// true while block time is within the starts_at and expires_at of the token
+    pub fn nft_is_valid(&self, token_id: TokenId) -> bool {
+        let token_metadata = self.tokens.token_metadata_by_id.as_ref().unwrap().get(&token_id).expect("Token not found");
+        let now = env::block_timestamp();
+        parse_timestamp(&token_metadata.starts_at).is_none_or(|starts_at| now >= starts_at)
+            && parse_timestamp(&token_metadata.expires_at).is_none_or(|expires_at| now < expires_at)
+    }
+
+    /// None when the token can be transferred
+    pub fn nft_transfer_locked_until(&self, token_id: TokenId) -> Option<TimestampSec> {
+        self.transfer_locked_until
+            .get(&token_id)
+            .filter(|locked_until| to_sec(env::block_timestamp()) < *locked_until)
+    }
+
+    pub fn nft_token(&self, token_id: TokenId) -> Option<Token> {
+        let owner_id = self.tokens.owner_by_id.get(&token_id)?;
+        let approved_account_ids = self
+            .tokens
+            .approvals_by_id
+            .as_ref()
+            .and_then(|by_id| by_id.get(&token_id).or_else(|| Some(HashMap::new())));
+
+        // CUSTOM (switch metadata for the token_series metadata)
+        let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
+        let token_series_id = token_id_iter.next().unwrap().parse().unwrap();
+                let series_metadata = self.token_series_by_id.get(&token_series_id).unwrap().metadata;
+
+        let mut token_metadata = self.tokens.token_metadata_by_id.as_ref().unwrap().get(&token_id).unwrap();
+
+        token_metadata.title = Some(format!(
+            "{}{}{}",
+            series_metadata.title.unwrap(),
+            TITLE_DELIMETER,
+            token_id_iter.next().unwrap()
+        ));
+
+        token_metadata.reference = series_metadata.reference;
+        token_metadata.media = series_metadata.media;
+        token_metadata.copies = series_metadata.copies;
+        token_metadata.extra = series_metadata.extra;
+
+        Some(Token {
+            token_id,
+            owner_id,
+            metadata: Some(token_metadata),
+            approved_account_ids,
+        })
+    }
+
+    #[payable]
+    pub fn nft_transfer(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.assert_not_paused(PausableFeature::Transfers);
+        let sender_id = env::predecessor_account_id();
+        let receiver_id_str = receiver_id.to_string();
+        let (previous_owner_id, _) = self.internal_transfer(
+            &sender_id,
+            &receiver_id_str,
+            &token_id,
+            approval_id,
+            memo.clone(),
+        );
+
+        let authorized_id : Option<AccountId> = if sender_id != previous_owner_id {
+            Some(sender_id)
+        } else {
+             None
+        };
+
+        NearEvent::log_nft_transfer(
+            previous_owner_id,
+            receiver_id_str,
+            vec![token_id],
+            memo,
+             authorized_id,
+        );
+    }
+
+    #[payable]
+    pub fn nft_transfer_call(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        assert_one_yocto();
+        self.assert_not_paused(PausableFeature::Transfers);
+        let sender_id = env::predecessor_account_id();
+        let (previous_owner_id, old_approvals) = self.internal_transfer(
+            &sender_id,
+            receiver_id.as_ref(),
+            &token_id,
+            approval_id,
+            memo.clone(),
+        );
+
+        let authorized_id : Option<AccountId> = if sender_id != previous_owner_id {
+            Some(sender_id.clone())
+        } else {
+            None
+        };
+
+        NearEvent::log_nft_transfer(
+            previous_owner_id.clone(),
+            receiver_id.to_string(),
+            vec![token_id.clone()],
+            memo,
+            authorized_id,
+        );
+
+        // Initiating receiver's call and the callback
+        ext_non_fungible_token_receiver::nft_on_transfer(
+            sender_id,
+            previous_owner_id.clone(),
+            token_id.clone(),
+            msg,
+            receiver_id.as_ref(),
+            NO_DEPOSIT,
+            env::prepaid_gas() - GAS_FOR_NFT_TRANSFER_CALL,
+        )
+        .then(ext_self::nft_resolve_transfer(
+            previous_owner_id,
+            receiver_id.into(),
+            token_id,
+            old_approvals,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_TRANSFER,
+        ))
+        .into()
+    }
+
+    // CUSTOM enumeration standard modified here because no macro below
+
+    pub fn nft_total_supply(&self) -> U128 {
+        (self.tokens.owner_by_id.len() as u128).into()
+    }
+
+    pub fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
+        // Get starting index, whether or not it was explicitly given.
+        // Defaults to 0 based on the spec:
+        // https://nomicon.io/Standards/NonFungibleToken/Enumeration.html#interface
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        assert!(
+            (self.tokens.owner_by_id.len() as u128) > start_index,
+            "Out of bounds, please use a smaller from_index."
+        );
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        self.tokens
+            .owner_by_id
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|(token_id, _)| self.nft_token(token_id).unwrap())
+            .collect()
+    }
+
+    pub fn nft_supply_for_owner(self, account_id: ValidAccountId) -> U128 {
+        let tokens_per_owner = self.tokens.tokens_per_owner.expect(
+            "Could not find tokens_per_owner when calling a method on the enumeration standard.",
+        );
+        tokens_per_owner
+            .get(account_id.as_ref())
+            .map(|account_tokens| U128::from(account_tokens.len() as u128))
+            .unwrap_or(U128(0))
+    }
+
+    pub fn nft_tokens_for_owner(
+        &self,
+        account_id: ValidAccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token> {
+        let tokens_per_owner = self.tokens.tokens_per_owner.as_ref().expect(
+            "Could not find tokens_per_owner when calling a method on the enumeration standard.",
+        );
+        let token_set = if let Some(token_set) = tokens_per_owner.get(account_id.as_ref()) {
+            token_set
+        } else {
+            return vec![];
+        };
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        assert_ne!(limit, 0, "Cannot provide limit of 0.");
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        assert!(
+            token_set.len() as u128 > start_index,
+            "Out of bounds, please use a smaller from_index."
+        );
+        token_set
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|token_id| self.nft_token(token_id).unwrap())
+            .collect()
+    }
+
+    pub fn nft_payout(
+        &self, 
+        token_id: TokenId,
+        balance: U128, 
+        max_len_payout: Option<u32>
+    ) -> Payout{
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("No token id");
+        self.calculate_payout(&token_id, &owner_id, balance.into(), max_len_payout)
+    }
+
+    #[payable]
+    pub fn nft_transfer_payout(
+        &mut self, 
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        balance: Option<U128>,
+        max_len_payout: Option<u32>,
+        memo: Option<String>,
+    ) -> Option<Payout> {
+        assert_one_yocto();
+        self.assert_not_paused(PausableFeature::Transfers);
+
+        let sender_id = env::predecessor_account_id();
+        let previous_owner_id = self.tokens.owner_by_id.get(&token_id).expect("no token");
+
+        // Payout calculation
+        let payout = balance.map(|balance| {
+            self.calculate_payout(&token_id, &previous_owner_id, balance.into(), max_len_payout)
+        });
+
+        // Transfer
+        self.internal_transfer(&sender_id, receiver_id.as_ref(), &token_id, approval_id, memo.clone());
+
+        let authorized_id : Option<AccountId> = if sender_id != previous_owner_id {
+            Some(sender_id)
+        } else {
+            None
+        };
+
+        NearEvent::log_nft_transfer(
+            previous_owner_id,
+            receiver_id.to_string(),
+            vec![token_id],
+            memo,
+            authorized_id,
+        );
+
+        payout
+    }
+
+    /// NEP-199 payout of balance for a token owned by owner_id, royalty is paid first
+    /// and the owner receives the remainder including rounding dust
+    fn calculate_payout(
+        &self,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        balance: Balance,
+        max_len_payout: Option<u32>
+    ) -> Payout {
+        let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
+        let token_series_id: TokenSeriesId = token_id_iter.next().unwrap().parse().unwrap();
+        let royalty = self.token_series_by_id.get(&token_series_id).expect("no type").royalty;
+
+        let mut payout: Payout = Payout { payout: HashMap::new() };
+        let mut total_payout: Balance = 0;
+
+        for (k, v) in royalty.iter() {
+            if k != owner_id {
+                let amount = royalty_to_payout(*v, balance);
+                total_payout += amount.0;
+                payout.payout.insert(k.clone(), amount);
+            }
+        }
+
+        assert!(
+            total_payout <= balance,
+            "Total payout overflow"
+        );
+
+        payout.payout.insert(owner_id.clone(), U128(balance - total_payout));
+
+        if let Some(max_len_payout) = max_len_payout {
+            assert!(
+                payout.payout.len() as u32 <= max_len_payout,
+                "Market cannot payout to that many receivers"
+            );
+        }
+
+        payout
+    }
+
+    pub fn nft_is_operator(
+        &self,
+        owner_id: ValidAccountId,
+        account_id: ValidAccountId,
+        token_series_id: Option<TokenSeriesId>,
+    ) -> bool {
+        self.operator_approvals
+            .get(owner_id.as_ref())
+            .and_then(|operators| operators.get(account_id.as_ref()).cloned())
+            .map(|token_series_ids| match (token_series_ids, token_series_id) {
+                (None, _) => true,
+                (Some(token_series_ids), Some(token_series_id)) => token_series_ids.contains(&token_series_id),
+                (Some(_), None) => false,
+            })
+            .unwrap_or(false)
+    }
+
+    pub fn nft_operators(&self, owner_id: ValidAccountId) -> HashMap<AccountId, Option<Vec<TokenSeriesId>>> {
+        self.operator_approvals.get(owner_id.as_ref()).unwrap_or_default()
+    }
+
+    /// storage cost of one more approved account_id on a token that already has approvals,
+    /// the first approval of a token also stores its approvals entry
+    pub fn nft_approval_storage_cost(&self, account_id: ValidAccountId) -> U128 {
+        let bytes = account_id.as_ref().len() as u64 + 4 + std::mem::size_of::<u64>() as u64;
+        U128(env::storage_byte_cost() * Balance::from(bytes))
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.tokens.owner_id.clone()
+    }
+
+    /// credit freed storage to the storage balance of account_id if registered, otherwise transfer it
+    fn refund_storage(&mut self, account_id: &AccountId, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+        if let Some(storage_balance) = self.storage_deposits.get(account_id) {
+            self.storage_deposits.insert(account_id, &(storage_balance + amount));
+        } else {
+            Promise::new(account_id.clone()).transfer(amount);
+        }
+    }
+
+    /// transfer token_id from its owner, sender_id must be the owner, approved for the token
+    /// or an operator of the owner. Returns the previous owner and the cleared approvals
+    fn internal_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) -> (AccountId, Option<HashMap<AccountId, u64>>) {
+        self.assert_transferable(token_id);
+        self.assert_transfer_unlocked(token_id);
+        let owner_id = self.tokens.owner_by_id.get(token_id).expect("Token not found");
+        if sender_id == &owner_id || !self.is_operator(&owner_id, sender_id, token_id) {
+            return self.tokens.internal_transfer(sender_id, receiver_id, token_id, approval_id, memo);
+        }
+
+        assert_ne!(&owner_id, receiver_id, "Current and next owner must differ");
+        let approved_account_ids = self
+            .tokens
+            .approvals_by_id
+            .as_mut()
+            .and_then(|by_id| by_id.remove(token_id));
+        self.tokens.internal_transfer_unguarded(token_id, &owner_id, receiver_id);
+
+        (owner_id, approved_account_ids)
+    }
+
+    fn assert_transferable(&self, token_id: &TokenId) {
+        let token_series_id = token_id.split(TOKEN_DELIMETER).next().unwrap().to_string();
+        let token_series = self.token_series_by_id.get(&token_series_id).expect("Paras: Token series not exist");
+        assert!(token_series.transferable, "Paras: token is non-transferable");
+        if token_series.expiry_policy != ExpiryPolicy::Transferable {
+            assert!(!self.is_expired(token_id), "Paras: token is expired");
+        }
+    }
+
+    fn is_expired(&self, token_id: &TokenId) -> bool {
+        self.tokens
+            .token_metadata_by_id
+            .as_ref()
+            .and_then(|by_id| by_id.get(token_id))
+            .and_then(|token_metadata| parse_timestamp(&token_metadata.expires_at))
+            .is_some_and(|expires_at| env::block_timestamp() >= expires_at)
+    }
+
+    fn assert_transfer_unlocked(&mut self, token_id: &TokenId) {
+        if let Some(locked_until) = self.transfer_locked_until.get(token_id) {
+            assert!(
+                to_sec(env::block_timestamp()) >= locked_until,
+                "Paras: token is locked until {}",
+                locked_until
+            );
+            self.transfer_locked_until.remove(token_id);
+        }
+    }
+
+    fn is_operator(&self, owner_id: &AccountId, account_id: &AccountId, token_id: &TokenId) -> bool {
+        let token_series_id = token_id.split(TOKEN_DELIMETER).next().unwrap();
+        self.operator_approvals
+            .get(owner_id)
+            .and_then(|operators| operators.get(account_id).cloned())
+            .map(|token_series_ids| match token_series_ids {
+                None => true,
+                Some(token_series_ids) => token_series_ids.iter().any(|id| id == token_series_id),
+            })
+            .unwrap_or(false)
+    }
+
+    /// nft_is_approved of the standard takes self by value, this one can be called on a reference
+    fn internal_is_approved(&self, token_id: &TokenId, account_id: &AccountId, approval_id: Option<u64>) -> bool {
+        let owner_id = self.tokens.owner_by_id.get(token_id).expect("Token not found");
+        // operators have no approval_id, they are approved for any
+        if self.is_operator(&owner_id, account_id, token_id) {
+            return true;
+        }
+        let actual_approval_id = self.tokens
+            .approvals_by_id
+            .as_ref()
+            .and_then(|approvals_by_id| approvals_by_id.get(token_id))
+            .and_then(|approved_account_ids| approved_account_ids.get(account_id).cloned());
+        match (actual_approval_id, approval_id) {
+            (Some(actual_approval_id), Some(approval_id)) => actual_approval_id == approval_id,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// add account_id to the approvals of token_id, ownership is checked and storage is charged by the caller
+    fn internal_approve(&mut self, token_id: &TokenId, account_id: &AccountId) -> u64 {
+        self.assert_transferable(token_id);
+        let approvals_by_id = self.tokens.approvals_by_id.as_mut().unwrap();
+        let mut approved_account_ids = approvals_by_id.get(token_id).unwrap_or_default();
+        let approval_id: u64 =
+            self.tokens.next_approval_id_by_id.as_ref().unwrap().get(token_id).unwrap_or(1u64);
+        approved_account_ids.insert(account_id.clone(), approval_id);
+        approvals_by_id.insert(token_id, &approved_account_ids);
+        self.tokens.next_approval_id_by_id.as_mut().unwrap().insert(token_id, &(approval_id + 1));
+
+        approval_id
+    }
+
+    /// remove account_id, or every account when None, from the approvals of token_id
+    /// and refund the freed storage to the token owner
+    fn internal_revoke(&mut self, token_id: &TokenId, account_id: Option<&AccountId>) {
+        let initial_storage_usage = env::storage_usage();
+
+        let owner_id = self.tokens.owner_by_id.get(token_id).expect("Token not found");
+        assert_eq!(
+            env::predecessor_account_id(),
+            owner_id,
+            "Predecessor must be the token owner."
+        );
+
+        let approvals_by_id = self.tokens.approvals_by_id.as_mut().unwrap();
+        let mut approved_account_ids = approvals_by_id.get(token_id).unwrap_or_default();
+        let revoked_account_ids: Vec<AccountId> = match account_id {
+            Some(account_id) => approved_account_ids
+                .remove(account_id)
+                .map(|_| vec![account_id.clone()])
+                .unwrap_or_default(),
+            None => approved_account_ids.drain().map(|(account_id, _)| account_id).collect(),
+        };
+        if revoked_account_ids.is_empty() {
+            return;
+        }
+
+        if approved_account_ids.is_empty() {
+            approvals_by_id.remove(token_id);
+        } else {
+            approvals_by_id.insert(token_id, &approved_account_ids);
+        }
+
+        let freed_storage = initial_storage_usage.saturating_sub(env::storage_usage());
+        self.refund_storage(&owner_id, env::storage_byte_cost() * Balance::from(freed_storage));
+
+        NearEvent::log_nft_revoke(token_id.clone(), owner_id, revoked_account_ids);
+    }
+
+    /// charge storage used since initial_storage_usage, or refund it to the predecessor if it was freed
+    fn charge_storage_delta(&mut self, initial_storage_usage: u64) {
+        let storage_usage = env::storage_usage();
+        if storage_usage >= initial_storage_usage {
+            self.charge_storage(storage_usage - initial_storage_usage, 0);
+        } else {
+            self.charge_storage(0, 0);
+            self.refund_storage(
+                &env::predecessor_account_id(),
+                env::storage_byte_cost() * Balance::from(initial_storage_usage - storage_usage),
+            );
+        }
+    }
+
+    /// draw the cost of storage_used from the storage balance of account_id, for calls without deposit
+    fn charge_storage_balance(&mut self, account_id: &AccountId, storage_used: u64) {
+        let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+        let storage_balance = self.storage_deposits.get(account_id).unwrap_or(0);
+        assert!(
+            required_cost <= storage_balance,
+            "Paras: storage balance of {} is less than the storage cost : {}",
+            account_id,
+            required_cost
+        );
+        self.storage_deposits.insert(account_id, &(storage_balance - required_cost));
+    }
+
+    /// same as refund_deposit, but storage cost not covered by the attached deposit
+    /// is drawn from the predecessor storage balance
+    fn charge_storage(&mut self, storage_used: u64, extra_spend: Balance) {
+        let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+        let attached_deposit = env::attached_deposit() - extra_spend;
+
+        if required_cost <= attached_deposit {
+            let refund = attached_deposit - required_cost;
+            if refund > 1 {
+                Promise::new(env::predecessor_account_id()).transfer(refund);
+            }
+            return;
+        }
+
+        let account_id = env::predecessor_account_id();
+        let storage_balance = self.storage_deposits.get(&account_id).unwrap_or(0);
+        let shortfall = required_cost - attached_deposit;
+        assert!(
+            shortfall <= storage_balance,
+            "Must attach {} yoctoNEAR to cover storage",
+            required_cost,
+        );
+        self.storage_deposits.insert(&account_id, &(storage_balance - shortfall));
+    }
+}
+
+fn royalty_to_payout(a: u32, b: Balance) -> U128 {
+    U128(a as u128 * b / 10_000u128)
+}
+
+/// validates accounts of a royalty-like map, returns the total of its shares
+fn validate_payees(payees: &HashMap<AccountId, u32>, name: &str) -> u32 {
+    let mut total: u32 = 0;
+    for (k, v) in payees.iter() {
+        if !is_valid_account_id(k.as_bytes()) {
+            env::panic(format!("Not valid account_id for {}", name).as_bytes());
+        };
+        total += *v;
+    }
+
+    assert!(payees.len() <= 50, "Paras: {} exceeds 50 accounts", name);
+
+    total
+}
+
+fn assert_valid_primary_split(primary_split: &HashMap<AccountId, u32>, ft_token_id: &Option<AccountId>) {
+    let total = validate_payees(primary_split, "primary_split");
+    assert!(
+        ft_token_id.is_none() || primary_split.len() <= MAX_FT_PRIMARY_SPLIT_LEN,
+        "Paras: primary_split of a series priced in fungible token exceeds {} accounts",
+        MAX_FT_PRIMARY_SPLIT_LEN
+    );
+    assert!(
+        primary_split.is_empty() || total == 10_000,
+        "Paras: primary_split must sum to 10000"
+    );
+}
+
+// near_contract_standards::impl_non_fungible_token_core!(Contract, tokens);
+// near_contract_standards::impl_non_fungible_token_enumeration!(Contract, tokens);
+// near_contract_standards::impl_non_fungible_token_approval!(Contract, tokens);
+
+#[near_bindgen]
+impl NonFungibleTokenApproval for Contract {
+    #[payable]
+    fn nft_approve(
+        &mut self,
+        token_id: TokenId,
+        account_id: ValidAccountId,
+        msg: Option<String>,
+    ) -> Option<Promise> {
+        assert!(
+            env::attached_deposit() >= 1,
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+
+        let owner_id = self.tokens.owner_by_id.get(&token_id).expect("Token not found");
+        assert_eq!(
+            env::predecessor_account_id(),
+            owner_id,
+            "Predecessor must be the token owner."
+        );
+
+        let account_id: AccountId = account_id.into();
+        let approval_id = self.internal_approve(&token_id, &account_id);
+
+        self.charge_storage(env::storage_usage() - initial_storage_usage, 0);
+
+        NearEvent::log_nft_approve(token_id.clone(), owner_id.clone(), account_id.clone(), approval_id);
+
+        msg.map(|msg| {
+            ext_approval_receiver::nft_on_approve(
+                token_id,
+                owner_id,
+                approval_id,
+                msg,
+                &account_id,
+                NO_DEPOSIT,
+                env::prepaid_gas() - GAS_FOR_NFT_APPROVE,
+            )
+        })
+    }
+
+    #[payable]
+    fn nft_revoke(&mut self, token_id: TokenId, account_id: ValidAccountId) {
+        assert_one_yocto();
+        self.internal_revoke(&token_id, Some(account_id.as_ref()));
+    }
+
+    #[payable]
+    fn nft_revoke_all(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        self.internal_revoke(&token_id, None);
+    }
+
+    fn nft_is_approved(
+        self,
+        token_id: TokenId,
+        approved_account_id: ValidAccountId,
+        approval_id: Option<u64>,
+    ) -> bool {
+        self.internal_is_approved(&token_id, approved_account_id.as_ref(), approval_id)
+    }
+}
+
+#[near_bindgen]
+impl StorageManagement for Contract {
+    /// storage balance is spent by methods that need more storage than the attached deposit
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<ValidAccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id: AccountId = if let Some(account_id) = account_id {
+            account_id.into()
+        } else {
+            env::predecessor_account_id()
+        };
+        let registration_cost = self.storage_balance_bounds().min.0;
+
+        let storage_balance = if let Some(storage_balance) = self.storage_deposits.get(&account_id) {
+            if registration_only == Some(true) {
+                if amount > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(amount);
+                }
+                storage_balance
+            } else {
+                storage_balance + amount
+            }
+        } else {
+            assert!(
+                amount >= registration_cost,
+                "Paras: attached deposit is less than the minimum storage balance : {}",
+                registration_cost
+            );
+            if registration_only == Some(true) {
+                let refund = amount - registration_cost;
+                if refund > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(refund);
+                }
+                0
+            } else {
+                amount - registration_cost
+            }
+        };
+        self.storage_deposits.insert(&account_id, &storage_balance);
+
+        StorageBalance {
+            total: storage_balance.into(),
+            available: storage_balance.into(),
+        }
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let storage_balance = self.storage_deposits.get(&account_id).expect("Paras: account is not registered");
+        let amount: Balance = amount.map(|amount| amount.0).unwrap_or(storage_balance);
+        assert!(
+            amount <= storage_balance,
+            "Paras: amount is more than the available storage balance : {}",
+            storage_balance
+        );
+
+        let storage_balance = storage_balance - amount;
+        self.storage_deposits.insert(&account_id, &storage_balance);
+        if amount > 0 {
+            Promise::new(account_id).transfer(amount);
+        }
+
+        StorageBalance {
+            total: storage_balance.into(),
+            available: storage_balance.into(),
+        }
+    }
+
+    /// refunds the storage balance together with the registration cost
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        // nothing else depends on the storage balance, so force makes no difference
+        let _ = force;
+        let account_id = env::predecessor_account_id();
+        if let Some(storage_balance) = self.storage_deposits.remove(&account_id) {
+            Promise::new(account_id).transfer(storage_balance + self.storage_balance_bounds().min.0);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: (env::storage_byte_cost() * Balance::from(STORAGE_BYTES_PER_ACCOUNT)).into(),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: ValidAccountId) -> Option<StorageBalance> {
+        self.storage_deposits.get(account_id.as_ref()).map(|storage_balance| StorageBalance {
+            total: storage_balance.into(),
+            available: storage_balance.into(),
+        })
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenMetadataProvider for Contract {
+    fn nft_metadata(&self) -> NFTContractMetadata {
+        self.metadata.get().unwrap()
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    /// Buy an edition of a series priced in the transferred fungible token.
+    /// msg : {"token_series_id": "1", "receiver_id": "alice.near"}, receiver_id defaults to sender_id.
+    /// Returns the unused amount, refunded by the fungible token contract.
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: ValidAccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let initial_storage_usage = env::storage_usage();
+        let ft_token_id = env::predecessor_account_id();
+        let NftBuyArgs { token_series_id, receiver_id } =
+            near_sdk::serde_json::from_str(&msg).expect("Paras: invalid msg");
+
+        let mut token_series = self.token_series_by_id.get(&token_series_id).expect("Paras: Token series not exist");
+        assert_eq!(
+            token_series.ft_token_id,
+            Some(ft_token_id.clone()),
+            "Paras: series is not priced in {}",
+            ft_token_id
+        );
+        let price: u128 = self._nft_series_sale_price(&token_series_id, &mut token_series, sender_id.as_ref(), 1);
+        assert!(
+            amount.0 >= price,
+            "Paras: transferred amount is less than price : {}",
+            price
+        );
+        let receiver_id: AccountId = if let Some(receiver_id) = receiver_id {
+            receiver_id.to_string()
+        } else {
+            sender_id.to_string()
+        };
+
+        let token_id: TokenId = self._nft_mint_series(token_series_id.clone(), receiver_id.clone(), sender_id.as_ref());
+
+        // no NEAR is attached to ft_on_transfer, a panic makes the fungible token contract refund the buyer
+        self.charge_storage_balance(sender_id.as_ref(), env::storage_usage() - initial_storage_usage);
+
+        self._nft_distribute_sale(&token_series_id, &token_series, price);
+
+        NearEvent::log_nft_mint(
+            receiver_id,
+            vec![token_id],
+            Some(json!({"price": price.to_string(), "ft_token_id": ft_token_id}).to_string())
+        );
+
+        PromiseOrValue::Value(U128(amount.0 - price))
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenResolver for Contract {
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, u64>>,
+    ) -> bool {
+        let resp: bool = self.tokens.nft_resolve_transfer(
+            previous_owner_id.clone(),
+            receiver_id.clone(),
+            token_id.clone(),
+            approved_account_ids,
+        );
+
+        // if not successful, return nft back to original owner
+        if !resp {
+            NearEvent::log_nft_transfer(
+                receiver_id,
+                previous_owner_id,
+                vec![token_id],
+                None,
+                None,
+            );
+        }
+
+        resp
+    }
+}
+
+/// from https://github.com/near/near-sdk-rs/blob/e4abb739ff953b06d718037aa1b8ab768db17348/near-contract-standards/src/non_fungible_token/utils.rs#L29
+
+fn refund_deposit(storage_used: u64, extra_spend: Balance) {
+    let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+    let attached_deposit = env::attached_deposit() - extra_spend;
+
+    assert!(
+        required_cost <= attached_deposit,
+        "Must attach {} yoctoNEAR to cover storage",
+        required_cost,
+    );
+
+    let refund = attached_deposit - required_cost;
+    if refund > 1 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+    }
+}
+
+/// notify that the metadata of the tokens of the series from from_index changed, the series id
+/// is added to the memo. Returns the number of tokens left past METADATA_UPDATE_TOKEN_IDS_PER_CALL
+fn log_series_metadata_update(token_series_id: &TokenSeriesId, token_series: &TokenSeries, from_index: u64) -> u64 {
+    let memo = Some(json!({ "token_series_id": token_series_id }).to_string());
+    let token_ids = token_series.tokens.as_vector();
+    if token_ids.is_empty() {
+        NearEvent::log_nft_metadata_update(vec![], memo);
+        return 0;
+    }
+
+    let to_index = std::cmp::min(from_index.saturating_add(METADATA_UPDATE_TOKEN_IDS_PER_CALL), token_ids.len());
+    let mut index = from_index;
+    while index < to_index {
+        let log_to_index = std::cmp::min(index + METADATA_UPDATE_TOKEN_IDS_PER_LOG, to_index);
+        NearEvent::log_nft_metadata_update(
+            (index..log_to_index).map(|index| token_ids.get(index).unwrap()).collect(),
+            memo.clone(),
+        );
+        index = log_to_index;
+    }
+    token_ids.len() - to_index
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn transfer_proceeds(ft_token_id: &Option<AccountId>, receiver_id: AccountId, amount: Balance) {
+    if let Some(ft_token_id) = ft_token_id {
+        ext_fungible_token::ft_transfer(
+            receiver_id.clone(),
+            U128(amount),
+            None,
+            ft_token_id,
+            1,
+            GAS_FOR_FT_TRANSFER,
+        )
+        .then(ext_self::ft_resolve_payout(
+            ft_token_id.clone(),
+            receiver_id,
+            U128(amount),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_FT_PAYOUT,
+        ));
+    } else {
+        Promise::new(receiver_id).transfer(amount);
+    }
+}
+
+/// series left in the previous layout cannot be read, everything but series creation waits
+/// for migrate_token_series, new series are stored past the ones to migrate
+fn is_paused_by_series_migration(feature: PausableFeature) -> bool {
+    feature != PausableFeature::SeriesCreation && migration::read_series_migration().is_some()
+}
+
+fn assert_batch_size(size: u64) {
+    assert!(
+        size <= MAX_MINT_BATCH_SIZE,
+        "Paras: cannot mint more than {} editions at once",
+        MAX_MINT_BATCH_SIZE
+    );
+}
+
+/// editions priced at 0 in any fungible token could be minted with a worthless token
+fn assert_valid_ft_price(ft_token_id: &Option<AccountId>, price: Balance) {
+    assert!(
+        ft_token_id.is_none() || price > 0,
+        "Paras: price in fungible token must be greater than 0"
+    );
+}
+
+fn assert_valid_sale_window(sale_start: Option<TimestampSec>, sale_end: Option<TimestampSec>) {
+    if let (Some(sale_start), Some(sale_end)) = (sale_start, sale_end) {
+        assert!(sale_start < sale_end, "Paras: sale_start must be before sale_end");
+    }
+}
+
+fn assert_sale_open(token_series: &TokenSeries) {
+    let now = to_sec(env::block_timestamp());
+    if let Some(sale_start) = token_series.sale_start {
+        assert!(now >= sale_start, "Paras: sale has not started, starts at {}", sale_start);
+    }
+    if let Some(sale_end) = token_series.sale_end {
+        assert!(now < sale_end, "Paras: sale has ended at {}", sale_end);
+    }
+}
+
+fn series_current_price(token_series: &TokenSeries) -> Option<Balance> {
+    if let Some(dutch_auction) = &token_series.dutch_auction {
+        let now = to_sec(env::block_timestamp());
+        if now <= dutch_auction.start_time {
+            return Some(dutch_auction.start_price.0);
+        }
+        let steps = ((now - dutch_auction.start_time) / dutch_auction.decay_interval) as u128;
+        let price = dutch_auction.start_price.0
+            .saturating_sub(steps.saturating_mul(dutch_auction.price_decrement.0));
+        return Some(std::cmp::max(price, dutch_auction.floor_price.0));
+    }
+    token_series.price
+}
+
+fn to_sec(timestamp: Timestamp) -> TimestampSec {
+    (timestamp / 10u64.pow(9)) as u32
+}
+
+/// NEP-177 starts_at and expires_at are Unix epoch milliseconds, returned in nanoseconds like
+/// block_timestamp. Other formats, e.g. ISO 8601, are kept as they are but not enforced
+fn parse_timestamp(timestamp: &Option<String>) -> Option<Timestamp> {
+    timestamp
+        .as_ref()
+        .and_then(|timestamp| timestamp.parse::<u64>().ok())
+        .map(|timestamp| timestamp.saturating_mul(1_000_000))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{testing_env};
+
+    const STORAGE_FOR_CREATE_SERIES: Balance = 8540000000000000000000;
+    const STORAGE_FOR_MINT: Balance = 11280000000000000000000;
+
+    fn get_context(predecessor_account_id: ValidAccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .signer_account_id(predecessor_account_id.clone())
+            .predecessor_account_id(predecessor_account_id);
+        builder
+    }
 
     fn setup_contract() -> (VMContextBuilder, Contract) {
         let mut context = VMContextBuilder::new();
@@ -1205,274 +3343,2358 @@ mod tests {
     }
 
     #[test]
-    fn test_new() {
-        let mut context = get_context(accounts(1));
-        testing_env!(context.build());
-        let contract = Contract::new(
-            accounts(1),
-            accounts(4),
-            NFTContractMetadata {
-                spec: NFT_METADATA_SPEC.to_string(),
-                name: "Triple Triad".to_string(),
-                symbol: "TRIAD".to_string(),
-                icon: Some(DATA_IMAGE_SVG_PARAS_ICON.to_string()),
-                base_uri: Some("https://ipfs.fleek.co/ipfs/".to_string()),
-                reference: None,
-                reference_hash: None,
-            },
-            500
+    fn test_new() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let contract = Contract::new(
+            accounts(1),
+            accounts(4),
+            NFTContractMetadata {
+                spec: NFT_METADATA_SPEC.to_string(),
+                name: "Triple Triad".to_string(),
+                symbol: "TRIAD".to_string(),
+                icon: Some(DATA_IMAGE_SVG_PARAS_ICON.to_string()),
+                base_uri: Some("https://ipfs.fleek.co/ipfs/".to_string()),
+                reference: None,
+                reference_hash: None,
+            },
+            500
+        );
+        testing_env!(context.is_view(true).build());
+        assert_eq!(contract.get_owner(), accounts(1).to_string());
+        assert_eq!(contract.nft_metadata().base_uri.unwrap(), "https://ipfs.fleek.co/ipfs/".to_string());
+        assert_eq!(contract.nft_metadata().icon.unwrap(), DATA_IMAGE_SVG_PARAS_ICON.to_string());
+    }
+
+    fn sample_token_metadata(copies: Option<u64>) -> TokenMetadata {
+        TokenMetadata {
+            title: Some("Tsundere land".to_string()),
+            description: None,
+            media: Some(
+                "bafybeidzcan4nzcz7sczs4yzyxly4galgygnbjewipj6haco4kffoqpkiy".to_string()
+            ),
+            media_hash: None,
+            copies: copies,
+            issued_at: None,
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: None,
+            reference: Some(
+                "bafybeicg4ss7qh5odijfn2eogizuxkrdh3zlv4eftcmgnljwu7dm64uwji".to_string()
+            ),
+            reference_hash: None,
+        }
+    }
+
+    fn create_series(
+        contract: &mut Contract,
+        royalty: &HashMap<AccountId, u32>,
+        price: Option<U128>,
+        copies: Option<u64>,
+    ) {
+        contract.nft_create_series(
+            sample_token_metadata(copies),
+            price,
+            Some(royalty.clone()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn test_create_series() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1 * 10u128.pow(24))),
+            None
+        );
+
+        let nft_series_return = contract.nft_get_series_single("1".to_string());
+        assert_eq!(
+            nft_series_return.creator_id,
+            accounts(1).to_string()
+        );
+
+        assert_eq!(
+            nft_series_return.token_series_id,
+            "1",
+        );
+
+        assert_eq!(
+            nft_series_return.royalty,
+            royalty,
+        );
+
+        assert!(
+            nft_series_return.metadata.copies.is_none()
+        );
+
+        assert_eq!(
+            nft_series_return.metadata.title.unwrap(),
+            "Tsundere land".to_string()
+        );
+
+        assert_eq!(
+            nft_series_return.metadata.reference.unwrap(),
+            "bafybeicg4ss7qh5odijfn2eogizuxkrdh3zlv4eftcmgnljwu7dm64uwji".to_string()
+        );
+
+    }
+
+    #[test]
+    fn test_buy() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(
+            &mut contract,
+            &royalty,
+            Some(U128::from(1 * 10u128.pow(24))),
+            None
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_buy("1".to_string(), Some(accounts(3)));
+
+        let token_from_nft_token = contract.nft_token(token_id);
+        assert_eq!(
+            token_from_nft_token.unwrap().owner_id,
+            accounts(3).to_string()
+        );
+
+        let token_id = contract.nft_buy("1".to_string(), None);
+
+        let token_from_nft_token = contract.nft_token(token_id);
+        assert_eq!(
+            token_from_nft_token.unwrap().owner_id,
+            accounts(2).to_string()
+        );
+        // no max_per_account, purchases are not counted
+        assert!(contract.series_mints_by_account.get(&("1".to_string(), accounts(2).to_string())).is_none());
+    }
+
+    fn create_ft_series(contract: &mut Contract, ft_token_id: ValidAccountId, price: U128) {
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        contract.nft_create_series(
+            sample_token_metadata(None),
+            Some(price),
+            Some(royalty),
+            Some(ft_token_id),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn test_buy_with_ft() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        create_ft_series(&mut contract, accounts(5), U128::from(1_000_000));
+
+        let nft_series_return = contract.nft_get_series_single("1".to_string());
+        assert_eq!(nft_series_return.ft_token_id, Some(accounts(5).to_string()));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(STORAGE_FOR_MINT * 2)
+            .build()
+        );
+
+        contract.storage_deposit(None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(5))
+            .attached_deposit(0)
+            .build()
+        );
+
+        let refund = contract.ft_on_transfer(
+            accounts(2),
+            U128::from(1_500_000),
+            json!({"token_series_id": "1", "receiver_id": accounts(3)}).to_string(),
+        );
+
+        match refund {
+            PromiseOrValue::Value(refund) => assert_eq!(refund.0, 500_000),
+            PromiseOrValue::Promise(_) => panic!("expected value"),
+        }
+
+        let token = contract.nft_token("1:1".to_string()).unwrap();
+        assert_eq!(token.owner_id, accounts(3).to_string());
+        assert!(contract.storage_balance_of(accounts(2)).unwrap().total.0 < STORAGE_FOR_MINT * 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: storage balance of bob is less than the storage cost")]
+    fn test_invalid_buy_with_ft_without_storage() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        create_ft_series(&mut contract, accounts(5), U128::from(1_000_000));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(5))
+            .attached_deposit(0)
+            .build()
+        );
+
+        contract.ft_on_transfer(
+            accounts(1),
+            U128::from(1_000_000),
+            json!({"token_series_id": "1"}).to_string(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: price in fungible token must be greater than 0")]
+    fn test_invalid_create_ft_series_zero_price() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        create_ft_series(&mut contract, accounts(5), U128::from(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: series is not priced in charlie")]
+    fn test_invalid_buy_with_wrong_ft() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        create_ft_series(&mut contract, accounts(5), U128::from(1_000_000));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(0)
+            .build()
+        );
+
+        contract.ft_on_transfer(
+            accounts(2),
+            U128::from(1_000_000),
+            json!({"token_series_id": "1"}).to_string(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: series is priced in fungible token, use ft_transfer_call")]
+    fn test_invalid_buy_ft_series_with_near() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        create_ft_series(&mut contract, accounts(5), U128::from(1_000_000));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.nft_buy("1".to_string(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: sale has not started, starts at 1618109222")]
+    fn test_invalid_buy_before_sale_start() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .block_timestamp(1618109122863866400)
+            .build()
+        );
+
+        contract.nft_create_series(
+            sample_token_metadata(None),
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+            None,
+            Some(1618109222),
+            None,
+            None,
+            None,
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.nft_buy("1".to_string(), None);
+    }
+
+    #[test]
+    fn test_buy_within_sale_window() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .block_timestamp(1618109122863866400)
+            .build()
+        );
+
+        contract.nft_create_series(
+            sample_token_metadata(None),
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_set_series_sale_window("1".to_string(), Some(1618109222), Some(1618109322));
+
+        let series = contract.nft_get_series_single("1".to_string());
+        assert_eq!(series.sale_start, Some(1618109222));
+        assert_eq!(series.sale_end, Some(1618109322));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .block_timestamp(1618109222 * 10u64.pow(9))
+            .build()
+        );
+
+        let token_id = contract.nft_buy("1".to_string(), None);
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(2).to_string());
+    }
+
+    fn setup_presale(context: &mut VMContextBuilder, contract: &mut Contract) {
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .block_timestamp(1618109122863866400)
+            .build()
+        );
+
+        contract.nft_create_series(
+            sample_token_metadata(None),
+            Some(U128::from(2 * 10u128.pow(24))),
+            None,
+            None,
+            Some(1618109222),
+            None,
+            None,
+            None,
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_set_series_presale_price("1".to_string(), Some(U128::from(1 * 10u128.pow(24))));
+
+        let mut allowlist: HashMap<AccountId, u32> = HashMap::new();
+        allowlist.insert(accounts(2).to_string(), 1);
+        allowlist.insert(accounts(3).to_string(), 2);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        contract.nft_add_series_allowlist("1".to_string(), allowlist);
+    }
+
+    #[test]
+    fn test_buy_presale() {
+        let (mut context, mut contract) = setup_contract();
+        setup_presale(&mut context, &mut contract);
+
+        assert_eq!(contract.nft_get_series_allowlist_status("1".to_string(), accounts(2)), 1);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_buy("1".to_string(), None);
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(2).to_string());
+        assert_eq!(contract.nft_get_series_allowlist_status("1".to_string(), accounts(2)), 0);
+        assert_eq!(contract.nft_get_series_allowlist_status("1".to_string(), accounts(3)), 2);
+    }
+
+    #[test]
+    fn test_remove_series_allowlist_refunds_storage_balance() {
+        let (mut context, mut contract) = setup_contract();
+        setup_presale(&mut context, &mut contract);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.storage_deposit(None, Some(true));
+        assert_eq!(contract.storage_balance_of(accounts(1)).unwrap().total.0, 0);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_remove_series_allowlist("1".to_string(), vec![accounts(2).to_string()]);
+        assert_eq!(contract.nft_get_series_allowlist_status("1".to_string(), accounts(2)), 0);
+        assert!(contract.storage_balance_of(accounts(1)).unwrap().total.0 > 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: presale allocation exceeded, remaining : 0")]
+    fn test_invalid_buy_presale_not_in_allowlist() {
+        let (mut context, mut contract) = setup_contract();
+        setup_presale(&mut context, &mut contract);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_remove_series_allowlist("1".to_string(), vec![accounts(2).to_string()]);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.nft_buy("1".to_string(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: mint limit per account exceeded, remaining : 1")]
+    fn test_invalid_buy_above_max_per_account() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        contract.nft_create_series(
+            sample_token_metadata(None),
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+            None,
+            None,
+            None,
+            Some(2),
+            None,
+            None,
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(2 * 10u128.pow(24) + STORAGE_FOR_MINT * 2)
+            .build()
+        );
+
+        contract.nft_buy("1".to_string(), Some(accounts(3)));
+        assert_eq!(
+            contract.nft_get_series_remaining_for_account("1".to_string(), accounts(2)),
+            Some(1)
+        );
+        contract.nft_buy_batch("1".to_string(), 2, None);
+    }
+
+    fn setup_auction(context: &mut VMContextBuilder, contract: &mut Contract) {
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .block_timestamp(1618109122863866400)
+            .build()
+        );
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(contract, &royalty, None, Some(10));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        contract.nft_start_auction("1".to_string(), U128::from(1 * 10u128.pow(24)), 1618112722);
+    }
+
+    #[test]
+    fn test_auction() {
+        let (mut context, mut contract) = setup_contract();
+        setup_auction(&mut context, &mut contract);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24))
+            .build()
+        );
+        contract.nft_place_bid("1".to_string());
+
+        // bid placed right before the end extends the auction
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(2 * 10u128.pow(24))
+            .block_timestamp(1618112622 * 10u64.pow(9))
+            .build()
+        );
+        contract.nft_place_bid("1".to_string());
+
+        let auction = contract.nft_get_series_auction("1".to_string()).unwrap();
+        assert_eq!(auction.end_time, 1618112622 + AUCTION_EXTENSION_SEC);
+        assert_eq!(auction.highest_bidder_id, Some(accounts(3).to_string()));
+        assert_eq!(auction.highest_bid, Some(U128::from(2 * 10u128.pow(24))));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(0)
+            .block_timestamp((1618112622 + AUCTION_EXTENSION_SEC as u64) * 10u64.pow(9))
+            .build()
+        );
+        let token_id = contract.nft_settle_auction("1".to_string()).unwrap();
+
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(3).to_string());
+        assert!(contract.nft_get_series_auction("1".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_settle_auction_refunds_storage_balance() {
+        let (mut context, mut contract) = setup_contract();
+        setup_auction(&mut context, &mut contract);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.storage_deposit(None, Some(true));
+        assert_eq!(contract.storage_balance_of(accounts(1)).unwrap().total.0, 0);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(0)
+            .block_timestamp(1618112722 * 10u64.pow(9))
+            .build()
+        );
+
+        assert!(contract.nft_settle_auction("1".to_string()).is_none());
+        assert!(contract.storage_balance_of(accounts(1)).unwrap().total.0 > 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: bid is less than 1050000000000000000000000")]
+    fn test_invalid_bid_below_min_increment() {
+        let (mut context, mut contract) = setup_contract();
+        setup_auction(&mut context, &mut contract);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24))
+            .build()
+        );
+        contract.nft_place_bid("1".to_string());
+
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(1 * 10u128.pow(24) + 1)
+            .build()
+        );
+        contract.nft_place_bid("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: reserve_price is less than 100000000000000000000000")]
+    fn test_invalid_auction_reserve_price_too_low() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, Some(10));
+
+        contract.nft_start_auction("1".to_string(), U128::from(0), 1618112722);
+    }
+
+    #[test]
+    fn test_buy_dutch_auction() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .block_timestamp(1618109122 * 10u64.pow(9))
+            .build()
+        );
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_set_series_dutch_auction("1".to_string(), DutchAuction {
+            start_price: U128::from(10 * 10u128.pow(24)),
+            floor_price: U128::from(4 * 10u128.pow(24)),
+            start_time: 1618109122,
+            decay_interval: 60,
+            price_decrement: U128::from(1 * 10u128.pow(24)),
+        });
+
+        assert_eq!(
+            contract.nft_get_series_current_price("1".to_string()),
+            Some(U128::from(10 * 10u128.pow(24)))
+        );
+
+        // 3 decay intervals later
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(7 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .block_timestamp((1618109122 + 190) * 10u64.pow(9))
+            .build()
+        );
+
+        assert_eq!(
+            contract.nft_get_series_current_price("1".to_string()),
+            Some(U128::from(7 * 10u128.pow(24)))
+        );
+        let token_id = contract.nft_buy("1".to_string(), None);
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(2).to_string());
+
+        // price never goes below floor_price
+        testing_env!(context
+            .block_timestamp((1618109122 + 6000) * 10u64.pow(9))
+            .build()
+        );
+
+        assert_eq!(
+            contract.nft_get_series_price("1".to_string()),
+            Some(U128::from(4 * 10u128.pow(24)))
+        );
+    }
+
+    #[test]
+    fn test_buy_with_primary_split() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut primary_split: HashMap<AccountId, u32> = HashMap::new();
+        primary_split.insert(accounts(1).to_string(), 7000);
+        primary_split.insert(accounts(3).to_string(), 3000);
+
+        contract.nft_create_series(
+            sample_token_metadata(None),
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(primary_split.clone()),
+            None,
+        );
+
+        let series = contract.nft_get_series_single("1".to_string());
+        assert_eq!(series.primary_split, primary_split);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_buy("1".to_string(), None);
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(2).to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: primary_split must sum to 10000")]
+    fn test_invalid_primary_split() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut primary_split: HashMap<AccountId, u32> = HashMap::new();
+        primary_split.insert(accounts(1).to_string(), 7000);
+        primary_split.insert(accounts(3).to_string(), 2000);
+
+        contract.nft_create_series(
+            sample_token_metadata(None),
+            Some(U128::from(1 * 10u128.pow(24))),
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(primary_split),
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: primary_split of a series priced in fungible token exceeds 8 accounts")]
+    fn test_invalid_ft_primary_split_too_long() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let primary_split: HashMap<AccountId, u32> = (0..10)
+            .map(|index| (format!("split{}.near", index), 1000))
+            .collect();
+
+        contract.nft_create_series(
+            sample_token_metadata(None),
+            Some(U128::from(1_000_000)),
+            None,
+            Some(accounts(5)),
+            None,
+            None,
+            None,
+            Some(primary_split),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_set_series_royalty() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut new_royalty: HashMap<AccountId, u32> = HashMap::new();
+        new_royalty.insert(accounts(1).to_string(), 500);
+        new_royalty.insert(accounts(3).to_string(), 500);
+        contract.nft_set_series_royalty("1".to_string(), new_royalty.clone());
+
+        assert_eq!(contract.nft_get_series_single("1".to_string()).royalty, new_royalty);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: royalty is locked")]
+    fn test_invalid_set_series_royalty_locked() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_lock_series_royalty("1".to_string());
+        assert!(contract.nft_get_series_single("1".to_string()).is_royalty_locked);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_set_series_royalty("1".to_string(), HashMap::new());
+    }
+
+    #[test]
+    fn test_mint() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        let token_from_nft_token = contract.nft_token(token_id);
+        assert_eq!(
+            token_from_nft_token.unwrap().owner_id,
+            accounts(2).to_string()
+        )
+    }
+
+    #[test]
+    fn test_mint_batch() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, Some(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT * 3)
+            .build()
+        );
+
+        let token_ids = contract.nft_mint_batch(
+            "1".to_string(),
+            vec![accounts(2), accounts(3), accounts(2)]
+        );
+
+        assert_eq!(token_ids, vec!["1:1".to_string(), "1:2".to_string(), "1:3".to_string()]);
+        assert_eq!(contract.nft_token("1:2".to_string()).unwrap().owner_id, accounts(3).to_string());
+        assert_eq!(contract.nft_supply_for_series("1".to_string()), U64::from(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: cannot mint more than 100 editions at once")]
+    fn test_invalid_buy_batch_above_max_batch_size() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, Some(U128::from(1)), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(STORAGE_FOR_MINT * 101)
+            .build()
+        );
+
+        contract.nft_buy_batch("1".to_string(), 101, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: not enough copies left : 2")]
+    fn test_invalid_buy_batch_above_copies() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, Some(U128::from(1 * 10u128.pow(24))), Some(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(3 * 10u128.pow(24) + STORAGE_FOR_MINT * 3)
+            .build()
+        );
+
+        contract.nft_buy_batch("1".to_string(), 3, None);
+    }
+
+    #[test]
+    fn test_buy_batch() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, Some(U128::from(1 * 10u128.pow(24))), Some(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(2 * 10u128.pow(24) + STORAGE_FOR_MINT * 2)
+            .build()
+        );
+
+        let token_ids = contract.nft_buy_batch("1".to_string(), 2, Some(accounts(3)));
+
+        assert_eq!(token_ids.len(), 2);
+        for token_id in token_ids {
+            assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(3).to_string());
+        }
+        assert!(contract.nft_get_series_price("1".to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: Token series is not mintable")]
+    fn test_invalid_mint_above_copies() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, Some(1));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.nft_mint("1".to_string(), accounts(2));
+        contract.nft_mint("1".to_string(), accounts(2));
+    }
+
+    #[test]
+    fn test_decrease_copies() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, Some(5));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.nft_mint("1".to_string(), accounts(2));
+        contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_decrease_series_copies("1".to_string(), U64::from(3));
+
+        assert_eq!(
+            near_sdk::test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_metadata_update","data":[{"token_ids":["1:1","1:2"],"memo":"{\"token_series_id\":\"1\"}"}]}"#
+        );
+
+        assert_eq!(contract.nft_log_series_metadata_update("1".to_string(), U64::from(1)), U64::from(0));
+        assert_eq!(
+            near_sdk::test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_metadata_update","data":[{"token_ids":["1:2"],"memo":"{\"token_series_id\":\"1\"}"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_update_series_metadata() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        create_series(&mut contract, &HashMap::new(), None, Some(5));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .block_timestamp(1_000)
+            .build()
+        );
+
+        let token_series = contract.nft_update_series_metadata(
+            "1".to_string(),
+            Some(SeriesMetadataUpdate {
+                title: None,
+                description: Some("fixed typo".to_string()),
+                media: None,
+                media_hash: None,
+                extra: None,
+                reference: None,
+                reference_hash: None,
+            }),
+            Some(true),
+        );
+
+        assert_eq!(token_series.metadata.title, Some("Tsundere land".to_string()));
+        assert_eq!(token_series.metadata.description, Some("fixed typo".to_string()));
+        assert_eq!(token_series.metadata.updated_at, Some("1000".to_string()));
+        assert!(token_series.is_metadata_frozen);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: cannot update metadata, already minted : 1")]
+    fn test_invalid_update_series_metadata_after_mint() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        create_series(&mut contract, &HashMap::new(), None, Some(5));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_update_series_metadata(
+            "1".to_string(),
+            Some(SeriesMetadataUpdate {
+                title: Some("New title".to_string()),
+                description: None,
+                media: None,
+                media_hash: None,
+                extra: None,
+                reference: None,
+                reference_hash: None,
+            }),
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: cannot decrease supply, already minted : 2")]
+    fn test_invalid_decrease_copies() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, Some(5));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.nft_mint("1".to_string(), accounts(2));
+        contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_decrease_series_copies("1".to_string(), U64::from(4));
+    }
+
+    #[test]
+    #[should_panic( expected = "Paras: not for sale" )]
+    fn test_invalid_buy_price_null() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, Some(U128::from(1 * 10u128.pow(24))), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_set_series_price("1".to_string(), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_buy("1".to_string(), Some(accounts(2)));
+
+        let token_from_nft_token = contract.nft_token(token_id);
+        assert_eq!(
+            token_from_nft_token.unwrap().owner_id,
+            accounts(2).to_string()
+        )
+    }
+
+    #[test]
+    #[should_panic( expected = "Paras: price higher than 1000000000000000000000000000000000" )]
+    fn test_invalid_price_shouldnt_be_higher_than_max_price() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, Some(U128::from(1_000_000_000 * 10u128.pow(24))), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+    }
+
+    #[test]
+    fn test_nft_burn() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_burn(token_id.clone());
+        let token = contract.nft_token(token_id);
+        assert!(token.is_none());
+    }
+
+    #[test]
+    fn test_nft_burn_refunds_storage_balance() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.storage_deposit(None, Some(true));
+        assert_eq!(contract.storage_balance_of(accounts(1)).unwrap().total.0, 0);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.storage_deposit(None, Some(true));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build()
+        );
+
+        // storage was paid by the creator who minted, not by the owner who burns
+        contract.nft_burn(token_id);
+        assert!(contract.storage_balance_of(accounts(1)).unwrap().total.0 > 0);
+        assert_eq!(contract.storage_balance_of(accounts(2)).unwrap().total.0, 0);
+    }
+
+    #[test]
+    fn test_nft_revoke_refunds_storage_balance() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.storage_deposit(None, Some(true));
+        contract.nft_approve(token_id.clone(), accounts(3), None);
+        assert!(contract.internal_is_approved(&token_id, &accounts(3).to_string(), Some(1)));
+        assert_eq!(contract.storage_balance_of(accounts(2)).unwrap().total.0, 0);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_revoke(token_id.clone(), accounts(3));
+        assert!(!contract.internal_is_approved(&token_id, &accounts(3).to_string(), None));
+        assert!(contract.storage_balance_of(accounts(2)).unwrap().total.0 > 0);
+    }
+
+    #[test]
+    fn test_nft_transfer_by_operator() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.nft_approve_all(accounts(3), Some("1".to_string()));
+        assert!(contract.nft_is_operator(accounts(2), accounts(3), Some("1".to_string())));
+        assert!(!contract.nft_is_operator(accounts(2), accounts(3), None));
+        assert!(contract.internal_is_approved(&token_id, &accounts(3).to_string(), None));
+        assert!(!contract.internal_is_approved(&token_id, &accounts(4).to_string(), None));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_transfer(accounts(4), token_id.clone(), None, None);
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(4).to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn test_invalid_nft_transfer_by_revoked_operator() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.nft_approve_all(accounts(3), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_revoke_operator(accounts(3), None);
+        assert!(contract.nft_operators(accounts(2)).is_empty());
+
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_transfer(accounts(4), token_id, None, None);
+    }
+
+    fn create_soulbound_series(contract: &mut Contract) {
+        contract.nft_create_series(
+            sample_token_metadata(None),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(false),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: token is non-transferable")]
+    fn test_invalid_transfer_soulbound() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        create_soulbound_series(&mut contract);
+        assert!(!contract.nft_get_series_single("1".to_string()).transferable);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_transfer(accounts(3), token_id, None, None);
+    }
+
+    #[test]
+    fn test_burn_soulbound() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        create_soulbound_series(&mut contract);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_burn(token_id.clone());
+        assert!(contract.nft_token(token_id).is_none());
+    }
+
+    #[test]
+    fn test_nft_transfer() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_transfer(accounts(3), token_id.clone(), None, None);
+
+        let token = contract.nft_token(token_id).unwrap();
+        assert_eq!(
+            token.owner_id,
+            accounts(3).to_string()
+        )
+    }
+
+    #[test]
+    fn test_nft_transfer_payout() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build()
+        );
+
+        let payout = contract.nft_transfer_payout(
+            accounts(3),
+            token_id.clone(),
+            Some(0) ,
+            Some(U128::from(1 * 10u128.pow(24))),
+            Some(10),
+            None,
+        );
+
+        let mut payout_calc: HashMap<AccountId, U128> = HashMap::new();
+        payout_calc.insert(
+            accounts(1).to_string(),
+            U128::from((1000 * (1 * 10u128.pow(24)))/10_000)
+        );
+        payout_calc.insert(
+            accounts(2).to_string(),
+            U128::from((9000 * (1 * 10u128.pow(24))) / 10_000)
+        );
+
+        assert_eq!(payout.unwrap().payout, payout_calc);
+
+        let token = contract.nft_token(token_id).unwrap();
+        assert_eq!(
+            token.owner_id,
+            accounts(3).to_string()
+        )
+    }
+
+    #[test]
+    fn test_nft_payout_rounding_to_owner() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
         );
-        testing_env!(context.is_view(true).build());
-        assert_eq!(contract.get_owner(), accounts(1).to_string());
-        assert_eq!(contract.nft_metadata().base_uri.unwrap(), "https://ipfs.fleek.co/ipfs/".to_string());
-        assert_eq!(contract.nft_metadata().icon.unwrap(), DATA_IMAGE_SVG_PARAS_ICON.to_string());
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 333);
+        royalty.insert(accounts(3).to_string(), 333);
+
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        let payout = contract.nft_payout(token_id, U128::from(1001), None).payout;
+        let total: u128 = payout.values().map(|amount| amount.0).sum();
+
+        assert_eq!(total, 1001);
+        assert_eq!(payout.get(&accounts(1).to_string()), Some(&U128::from(33)));
+        assert_eq!(payout.get(&accounts(2).to_string()), Some(&U128::from(935)));
     }
 
-    fn create_series(
-        contract: &mut Contract,
-        royalty: &HashMap<AccountId, u32>,
-        price: Option<U128>,
-        copies: Option<u64>,
-    ) {
-        contract.nft_create_series(
-            TokenMetadata {
-                title: Some("Tsundere land".to_string()),
-                description: None,
-                media: Some(
-                    "bafybeidzcan4nzcz7sczs4yzyxly4galgygnbjewipj6haco4kffoqpkiy".to_string()
-                ),
-                media_hash: None,
-                copies: copies,
-                issued_at: None,
-                expires_at: None,
-                starts_at: None,
-                updated_at: None,
-                extra: None,
-                reference: Some(
-                    "bafybeicg4ss7qh5odijfn2eogizuxkrdh3zlv4eftcmgnljwu7dm64uwji".to_string()
-                ),
-                reference_hash: None,
-            },
-            price,
-            Some(royalty.clone()),
+    #[test]
+    #[should_panic(expected = "Market cannot payout to that many receivers")]
+    fn test_invalid_nft_payout_max_len_includes_owner() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
+        royalty.insert(accounts(1).to_string(), 1000);
+
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+
+        contract.nft_payout(token_id, U128::from(1 * 10u128.pow(24)), Some(1));
+    }
+
+    #[test]
+    fn test_mint_with_storage_deposit() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES + STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let storage_balance = contract.storage_deposit(None, None);
+        let registration_cost = contract.storage_balance_bounds().min.0;
+        assert_eq!(storage_balance.total.0, STORAGE_FOR_CREATE_SERIES + STORAGE_FOR_MINT - registration_cost);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(0)
+            .build()
+        );
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(2).to_string());
+
+        let storage_balance_after = contract.storage_balance_of(accounts(1)).unwrap();
+        assert!(storage_balance_after.available.0 < storage_balance.available.0);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        let storage_balance = contract.storage_withdraw(None);
+        assert_eq!(storage_balance.available.0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Must attach")]
+    fn test_invalid_mint_without_storage() {
+        let (mut context, mut contract) = setup_contract();
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(0)
+            .build()
+        );
+
+        contract.nft_mint("1".to_string(), accounts(2));
+    }
+
+    #[test]
+    fn test_set_contract_metadata() {
+        let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let metadata = contract.set_contract_metadata(ContractMetadataUpdate {
+            base_uri: Some("https://ipfs.io/ipfs".to_string()),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+        });
+
+        assert_eq!(metadata.base_uri, Some("https://ipfs.io/ipfs".to_string()));
+        assert_eq!(contract.nft_metadata().base_uri, Some("https://ipfs.io/ipfs".to_string()));
+        assert_eq!(contract.nft_metadata().icon, Some(DATA_IMAGE_SVG_PARAS_ICON.to_string()));
+    }
+
+    #[test]
+    fn test_set_contract_metadata_reference_hash_only() {
+        let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        contract.set_contract_metadata(ContractMetadataUpdate {
+            base_uri: None,
+            icon: None,
+            reference: Some("bafybeicontractreference".to_string()),
+            reference_hash: Some(Base64VecU8(vec![1; 32])),
+        });
+
+        let metadata = contract.set_contract_metadata(ContractMetadataUpdate {
+            base_uri: None,
+            icon: None,
+            reference: None,
+            reference_hash: Some(Base64VecU8(vec![2; 32])),
+        });
+
+        assert_eq!(metadata.reference, Some("bafybeicontractreference".to_string()));
+        assert_eq!(contract.nft_metadata().reference_hash.map(|hash| hash.0), Some(vec![2; 32]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: Owner only")]
+    fn test_invalid_set_contract_metadata_not_owner() {
+        let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.set_contract_metadata(ContractMetadataUpdate {
+            base_uri: Some("https://ipfs.io/ipfs".to_string()),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+        });
+    }
+
+    #[test]
+    fn test_fee_manager_role() {
+        let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.grant_role(Role::FeeManager, accounts(2));
+        assert!(contract.has_role(Role::FeeManager, accounts(2)));
+        assert_eq!(contract.get_role_members(Role::FeeManager), vec![accounts(2).to_string()]);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.set_transaction_fee(100, None);
+        assert_eq!(contract.get_transaction_fee().current_fee, 100);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.revoke_role(Role::FeeManager, accounts(2));
+        assert!(!contract.has_role(Role::FeeManager, accounts(2)));
+    }
+
+    #[test]
+    fn test_mint_by_delegated_minter() {
+        let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.storage_deposit(None, Some(true));
+        contract.grant_role(Role::Minter, accounts(3));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_set_series_minter_delegation("1".to_string(), true);
+        assert!(contract.nft_get_series_single("1".to_string()).is_minter_delegated);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(2).to_string());
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build()
         );
+
+        contract.revoke_role(Role::Minter, accounts(3));
+        assert!(!contract.has_role(Role::Minter, accounts(3)));
+        assert!(contract.storage_balance_of(accounts(0)).unwrap().total.0 > 0);
     }
 
     #[test]
-    fn test_create_series() {
+    #[should_panic(expected = "Paras: not creator or delegated minter")]
+    fn test_invalid_mint_by_minter_not_delegated() {
         let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.grant_role(Role::Minter, accounts(3));
+
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_CREATE_SERIES)
             .build()
         );
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-        create_series(
-            &mut contract,
-            &royalty,
-            Some(U128::from(1 * 10u128.pow(24))),
-            None
-        );
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
+        create_series(&mut contract, &royalty, None, None);
 
-        let nft_series_return = contract.nft_get_series_single("1".to_string());
-        assert_eq!(
-            nft_series_return.creator_id,
-            accounts(1).to_string()
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
         );
 
-        assert_eq!(
-            nft_series_return.token_series_id,
-            "1",
-        );
+        contract.nft_mint("1".to_string(), accounts(2));
+    }
 
-        assert_eq!(
-            nft_series_return.royalty,
-            royalty,
+    #[test]
+    #[should_panic(expected = "Paras: Owner or Admin only")]
+    fn test_invalid_set_treasury_without_role() {
+        let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
         );
 
-        assert!(
-            nft_series_return.metadata.copies.is_none()
+        contract.grant_role(Role::FeeManager, accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build()
         );
 
-        assert_eq!(
-            nft_series_return.metadata.title.unwrap(),
-            "Tsundere land".to_string()
+        contract.set_treasury(accounts(3));
+    }
+
+    #[test]
+    fn test_transfer_ownership() {
+        let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build()
         );
 
-        assert_eq!(
-            nft_series_return.metadata.reference.unwrap(),
-            "bafybeicg4ss7qh5odijfn2eogizuxkrdh3zlv4eftcmgnljwu7dm64uwji".to_string()
+        contract.propose_owner(accounts(1));
+        assert_eq!(contract.get_owner(), accounts(0).to_string());
+        assert_eq!(contract.get_pending_owner(), Some(accounts(1).to_string()));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build()
         );
 
+        contract.accept_owner();
+        assert_eq!(contract.get_owner(), accounts(1).to_string());
+        assert!(contract.get_pending_owner().is_none());
     }
 
     #[test]
-    fn test_buy() {
+    #[should_panic(expected = "Paras: primary_sales is paused")]
+    fn test_invalid_buy_when_paused() {
         let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .build()
+        );
+
+        contract.grant_role(Role::Pauser, accounts(3));
+
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_CREATE_SERIES)
             .build()
         );
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-
         create_series(
             &mut contract,
-            &royalty,
+            &HashMap::new(),
             Some(U128::from(1 * 10u128.pow(24))),
             None
         );
 
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.set_paused(vec![PausableFeature::PrimarySales], true);
+        assert!(contract.is_paused(PausableFeature::PrimarySales));
+        assert!(!contract.is_paused(PausableFeature::Transfers));
+
         testing_env!(context
             .predecessor_account_id(accounts(2))
             .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
             .build()
         );
 
-        let token_id = contract.nft_buy("1".to_string(), Some(accounts(3)));
+        contract.nft_buy("1".to_string(), None);
+    }
 
-        let token_from_nft_token = contract.nft_token(token_id);
+    #[test]
+    fn test_unpause_transfers() {
+        let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.set_paused(vec![PausableFeature::Transfers, PausableFeature::Burns], true);
         assert_eq!(
-            token_from_nft_token.unwrap().owner_id,
-            accounts(3).to_string()
+            contract.get_paused_features(),
+            vec![PausableFeature::Transfers, PausableFeature::Burns]
         );
 
-        let token_id = contract.nft_buy("1".to_string(), None);
+        contract.set_paused(vec![PausableFeature::Transfers], false);
+        assert_eq!(contract.get_paused_features(), vec![PausableFeature::Burns]);
+    }
 
-        let token_from_nft_token = contract.nft_token(token_id);
-        assert_eq!(
-            token_from_nft_token.unwrap().owner_id,
-            accounts(2).to_string()
+    #[test]
+    fn test_change_transaction_fee_immediately() {
+        let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build()
         );
+
+        contract.set_transaction_fee(100, None);
+
+        assert_eq!(contract.get_transaction_fee().current_fee, 100);
     }
 
     #[test]
-    fn test_mint() {
+    fn test_change_transaction_fee_with_time() {
         let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build()
+        );
+
+        assert_eq!(contract.get_transaction_fee().current_fee, 500);
+        assert_eq!(contract.get_transaction_fee().next_fee, None);
+        assert_eq!(contract.get_transaction_fee().start_time, None);
+
+        let next_fee: u16 = 100;
+        let start_time: Timestamp = 1618109122863866400;
+        let start_time_sec: TimestampSec = to_sec(start_time);
+        contract.set_transaction_fee(next_fee, Some(start_time_sec));
+
+        assert_eq!(contract.get_transaction_fee().current_fee, 500);
+        assert_eq!(contract.get_transaction_fee().next_fee, Some(next_fee));
+        assert_eq!(contract.get_transaction_fee().start_time, Some(start_time_sec));
+
         testing_env!(context
             .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .block_timestamp(start_time + 1)
+            .build()
+        );
+
+        contract.calculate_current_transaction_fee();
+        assert_eq!(contract.get_transaction_fee().current_fee, next_fee);
+        assert_eq!(contract.get_transaction_fee().next_fee, None);
+        assert_eq!(contract.get_transaction_fee().start_time, None);
+    }
+
+    #[test]
+    fn test_transaction_fee_locked() {
+        let (mut context, mut contract) = setup_contract();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
             .build()
         );
 
+        assert_eq!(contract.get_transaction_fee().current_fee, 500);
+        assert_eq!(contract.get_transaction_fee().next_fee, None);
+        assert_eq!(contract.get_transaction_fee().start_time, None);
+
+        let next_fee: u16 = 100;
+        let start_time: Timestamp = 1618109122863866400;
+        let start_time_sec: TimestampSec = to_sec(start_time);
+        contract.set_transaction_fee(next_fee, Some(start_time_sec));
+
         let mut royalty: HashMap<AccountId, u32> = HashMap::new();
         royalty.insert(accounts(1).to_string(), 1000);
 
-        create_series(&mut contract, &royalty, None, None);
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .build()
+        );
+
+        create_series(&mut contract, &royalty, Some(U128::from(1 * 10u128.pow(24))), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.nft_set_series_price("1".to_string(), None);
+
+        assert_eq!(contract.get_transaction_fee().current_fee, 500);
+        assert_eq!(contract.get_transaction_fee().next_fee, Some(next_fee));
+        assert_eq!(contract.get_transaction_fee().start_time, Some(start_time_sec));
 
         testing_env!(context
             .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_MINT)
+            .block_timestamp(start_time + 1)
+            .attached_deposit(1)
+            .build()
+        );
+
+        contract.calculate_current_transaction_fee();
+        assert_eq!(contract.get_transaction_fee().current_fee, next_fee);
+        assert_eq!(contract.get_transaction_fee().next_fee, None);
+        assert_eq!(contract.get_transaction_fee().start_time, None);
+
+        let series = contract.nft_get_series_single("1".to_string());
+        let series_transaction_fee: u128 = series.transaction_fee.0;
+        assert_eq!(series_transaction_fee, 500);
+    }
+
+    fn write_legacy_state(owner_id: ValidAccountId) {
+        let mut token_series_by_id: UnorderedMap<TokenSeriesId, migration::TokenSeriesV1> =
+            UnorderedMap::new(StorageKey::TokenSeriesById);
+        let mut tokens = UnorderedSet::new(
+            StorageKey::TokensBySeriesInner {
+                token_series: "1".to_string(),
+            }
+            .try_to_vec()
+            .unwrap(),
+        );
+        tokens.insert(&"1:1".to_string());
+        token_series_by_id.insert(&"1".to_string(), &migration::TokenSeriesV1 {
+            metadata: sample_token_metadata(Some(10)),
+            creator_id: accounts(1).to_string(),
+            tokens,
+            price: Some(1 * 10u128.pow(24)),
+            is_mintable: true,
+            royalty: HashMap::new(),
+        });
+
+        let prev = migration::ContractV2 {
+            tokens: NonFungibleToken::new(
+                StorageKey::NonFungibleToken,
+                owner_id,
+                Some(StorageKey::TokenMetadata),
+                Some(StorageKey::Enumeration),
+                Some(StorageKey::Approval),
+            ),
+            metadata: LazyOption::new(StorageKey::Metadata, None),
+            token_series_by_id,
+            treasury_id: accounts(4).to_string(),
+            transaction_fee: TransactionFee {
+                next_fee: None,
+                start_time: None,
+                current_fee: 500,
+            },
+            market_data_transaction_fee: MarketDataTransactionFee {
+                transaction_fee: UnorderedMap::new(StorageKey::MarketDataTransactionFee),
+            },
+        };
+        env::state_write(&prev);
+    }
+
+    #[test]
+    fn test_migrate_legacy_state() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        write_legacy_state(accounts(0));
+        let mut contract = Contract::migrate(None);
+
+        assert_eq!(contract.get_state_version(), STATE_VERSION);
+        assert_eq!(contract.get_owner(), accounts(0).to_string());
+        assert_eq!(contract.get_transaction_fee().current_fee, 500);
+        assert_eq!(contract.get_token_series_to_migrate(), U64::from(1));
+
+        assert_eq!(contract.migrate_token_series(10), U64::from(0));
+        assert_eq!(contract.get_token_series_to_migrate(), U64::from(0));
+        assert!(!contract.is_paused(PausableFeature::Transfers));
+
+        let token_series = contract.nft_get_series_single("1".to_string());
+        assert_eq!(token_series.creator_id, accounts(1).to_string());
+        assert_eq!(token_series.presale_price, None);
+        assert!(!token_series.is_royalty_locked);
+        assert!(!token_series.is_metadata_frozen);
+        assert_eq!(contract.nft_supply_for_series("1".to_string()), U64::from(1));
+        assert_eq!(
+            contract.nft_get_series_price("1".to_string()),
+            Some(U128::from(1 * 10u128.pow(24)))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: transfers is paused until token series migration is done")]
+    fn test_invalid_transfer_during_series_migration() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        write_legacy_state(accounts(0));
+        let mut contract = Contract::migrate(None);
+        assert!(contract.is_paused(PausableFeature::Transfers));
+        assert!(contract.is_paused(PausableFeature::PrimarySales));
+        assert!(!contract.is_paused(PausableFeature::SeriesCreation));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
             .build()
         );
 
-        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+        contract.nft_transfer(accounts(3), "1:1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: state is already at version 3")]
+    fn test_invalid_migrate_twice() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.current_account_id(accounts(5)).build());
+
+        write_legacy_state(accounts(0));
+        let contract = Contract::migrate(None);
+        env::state_write(&contract);
+
+        Contract::migrate(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: Only owner")]
+    fn test_invalid_migrate_not_owner() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
 
-        let token_from_nft_token = contract.nft_token(token_id);
-        assert_eq!(
-            token_from_nft_token.unwrap().owner_id,
-            accounts(2).to_string()
-        )
+        write_legacy_state(accounts(0));
+        Contract::migrate(None);
     }
 
     #[test]
-    #[should_panic(expected = "Paras: Token series is not mintable")]
-    fn test_invalid_mint_above_copies() {
+    fn test_stage_and_deploy_upgrade() {
         let (mut context, mut contract) = setup_contract();
+
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(10u128.pow(24))
+            .block_timestamp(1_000 * 10u64.pow(9))
             .build()
         );
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-
-        create_series(&mut contract, &royalty, None, Some(1));
+        let staged_upgrade = contract.stage_upgrade(Base64VecU8::from(vec![0, 97, 115, 109]));
+        assert_eq!(staged_upgrade.code_hash, to_hex(&env::sha256(&[0, 97, 115, 109])));
+        assert_eq!(staged_upgrade.activates_at, 1_000 + DEFAULT_UPGRADE_DELAY_SEC);
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_MINT)
+            .attached_deposit(0)
+            .block_timestamp((1_000 + DEFAULT_UPGRADE_DELAY_SEC as u64) * 10u64.pow(9))
             .build()
         );
 
-        contract.nft_mint("1".to_string(), accounts(2));
-        contract.nft_mint("1".to_string(), accounts(2));
+        contract.deploy_upgrade();
+        assert!(contract.get_staged_upgrade().is_none());
     }
 
     #[test]
-    fn test_decrease_copies() {
+    fn test_cancel_upgrade_refunds_storage_balance() {
         let (mut context, mut contract) = setup_contract();
+
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(STORAGE_FOR_MINT)
             .build()
         );
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-
-        create_series(&mut contract, &royalty, None, Some(5));
+        contract.storage_deposit(None, Some(true));
+        assert_eq!(contract.storage_balance_of(accounts(0)).unwrap().total.0, 0);
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_MINT)
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(10u128.pow(24))
             .build()
         );
 
-        contract.nft_mint("1".to_string(), accounts(2));
-        contract.nft_mint("1".to_string(), accounts(2));
+        contract.stage_upgrade(Base64VecU8::from(vec![0; 1_000]));
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
+            .predecessor_account_id(accounts(0))
             .attached_deposit(1)
             .build()
         );
 
-        contract.nft_decrease_series_copies("1".to_string(), U64::from(3));
+        contract.cancel_upgrade();
+        assert!(contract.get_staged_upgrade().is_none());
+        assert!(contract.storage_balance_of(accounts(0)).unwrap().total.0 >= 1_000 * env::storage_byte_cost());
     }
 
     #[test]
-    #[should_panic(expected = "Paras: cannot decrease supply, already minted : 2")]
-    fn test_invalid_decrease_copies() {
+    #[should_panic(expected = "Paras: upgrade delay is less than 21600")]
+    fn test_invalid_set_upgrade_delay_below_min() {
         let (mut context, mut contract) = setup_contract();
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
             .build()
         );
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
+        contract.set_upgrade_delay(0);
+    }
 
-        create_series(&mut contract, &royalty, None, Some(5));
+    #[test]
+    #[should_panic(expected = "Paras: upgrade is timelocked until 87400")]
+    fn test_invalid_deploy_upgrade_before_delay() {
+        let (mut context, mut contract) = setup_contract();
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_MINT)
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(10u128.pow(24))
+            .block_timestamp(1_000 * 10u64.pow(9))
             .build()
         );
 
-        contract.nft_mint("1".to_string(), accounts(2));
-        contract.nft_mint("1".to_string(), accounts(2));
+        contract.stage_upgrade(Base64VecU8::from(vec![0, 97, 115, 109]));
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(1)
+            .attached_deposit(0)
+            .block_timestamp(2_000 * 10u64.pow(9))
             .build()
         );
 
-        contract.nft_decrease_series_copies("1".to_string(), U64::from(4));
+        contract.deploy_upgrade();
     }
 
     #[test]
-    #[should_panic( expected = "Paras: not for sale" )]
-    fn test_invalid_buy_price_null() {
-        let (mut context, mut contract) = setup_contract();
+    fn test_migrate_after_self_upgrade() {
+        let (mut context, contract) = setup_contract();
+        env::state_write(&contract);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(5))
+            .current_account_id(accounts(5))
+            .build()
+        );
+
+        let contract = Contract::migrate(None);
+        assert_eq!(contract.get_state_version(), STATE_VERSION);
+    }
+
+    fn mint_transfer_locked_token(context: &mut VMContextBuilder, contract: &mut Contract) -> TokenId {
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_CREATE_SERIES)
@@ -1481,8 +5703,7 @@ mod tests {
 
         let mut royalty: HashMap<AccountId, u32> = HashMap::new();
         royalty.insert(accounts(1).to_string(), 1000);
-
-        create_series(&mut contract, &royalty, Some(U128::from(1 * 10u128.pow(24))), None);
+        create_series(contract, &royalty, None, None);
 
         testing_env!(context
             .predecessor_account_id(accounts(1))
@@ -1490,47 +5711,57 @@ mod tests {
             .build()
         );
 
-        contract.nft_set_series_price("1".to_string(), None);
+        contract.nft_set_series_transfer_lock("1".to_string(), Some(100));
+        assert_eq!(contract.nft_get_series_single("1".to_string()).transfer_lock_sec, Some(100));
 
         testing_env!(context
-            .predecessor_account_id(accounts(2))
-            .attached_deposit(1 * 10u128.pow(24) + STORAGE_FOR_MINT)
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
+            .block_timestamp(1_000 * 10u64.pow(9))
             .build()
         );
 
-        let token_id = contract.nft_buy("1".to_string(), Some(accounts(2)));
-
-        let token_from_nft_token = contract.nft_token(token_id);
-        assert_eq!(
-            token_from_nft_token.unwrap().owner_id,
-            accounts(2).to_string()
-        )
+        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+        assert_eq!(contract.nft_transfer_locked_until(token_id.clone()), Some(1_100));
+        token_id
     }
 
     #[test]
-    #[should_panic( expected = "Paras: price higher than 1000000000000000000000000000000000" )]
-    fn test_invalid_price_shouldnt_be_higher_than_max_price() {
+    #[should_panic(expected = "Paras: token is locked until 1100")]
+    fn test_invalid_transfer_locked_token() {
         let (mut context, mut contract) = setup_contract();
+        let token_id = mint_transfer_locked_token(&mut context, &mut contract);
+
         testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .block_timestamp(1_050 * 10u64.pow(9))
             .build()
         );
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
+        contract.nft_transfer(accounts(3), token_id, None, None);
+    }
 
-        create_series(&mut contract, &royalty, Some(U128::from(1_000_000_000 * 10u128.pow(24))), None);
+    #[test]
+    fn test_transfer_after_lock() {
+        let (mut context, mut contract) = setup_contract();
+        let token_id = mint_transfer_locked_token(&mut context, &mut contract);
 
         testing_env!(context
-            .predecessor_account_id(accounts(1))
+            .predecessor_account_id(accounts(2))
             .attached_deposit(1)
+            .block_timestamp(1_100 * 10u64.pow(9))
             .build()
         );
+
+        assert_eq!(contract.nft_transfer_locked_until(token_id.clone()), None);
+        contract.nft_transfer(accounts(3), token_id.clone(), None, None);
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(3).to_string());
     }
 
     #[test]
-    fn test_nft_burn() {
+    #[should_panic(expected = "Paras: transfer_lock_sec is more than 315360000")]
+    fn test_invalid_set_transfer_lock_too_long() {
         let (mut context, mut contract) = setup_contract();
         testing_env!(context
             .predecessor_account_id(accounts(1))
@@ -1538,88 +5769,61 @@ mod tests {
             .build()
         );
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-
+        let royalty: HashMap<AccountId, u32> = HashMap::new();
         create_series(&mut contract, &royalty, None, None);
 
         testing_env!(context
             .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_MINT)
-            .build()
-        );
-
-        let token_id = contract.nft_mint("1".to_string(), accounts(2));
-
-        testing_env!(context
-            .predecessor_account_id(accounts(2))
             .attached_deposit(1)
             .build()
         );
 
-        contract.nft_burn(token_id.clone());
-        let token = contract.nft_token(token_id);
-        assert!(token.is_none());
+        contract.nft_set_series_transfer_lock("1".to_string(), Some(u32::MAX));
     }
 
-    #[test]
-    fn test_nft_transfer() {
-        let (mut context, mut contract) = setup_contract();
+    fn mint_ticket(context: &mut VMContextBuilder, contract: &mut Contract, expiry_policy: ExpiryPolicy) -> TokenId {
         testing_env!(context
             .predecessor_account_id(accounts(1))
             .attached_deposit(STORAGE_FOR_CREATE_SERIES)
             .build()
         );
 
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
-
-        create_series(&mut contract, &royalty, None, None);
+        let mut token_metadata = sample_token_metadata(None);
+        token_metadata.starts_at = Some((1_000 * 10u64.pow(3)).to_string());
+        token_metadata.expires_at = Some((2_000 * 10u64.pow(3)).to_string());
+        contract.nft_create_series(token_metadata, None, None, None, None, None, None, None, None);
 
         testing_env!(context
             .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_MINT)
+            .attached_deposit(1)
             .build()
         );
 
-        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+        contract.nft_set_series_expiry_policy("1".to_string(), expiry_policy);
 
         testing_env!(context
-            .predecessor_account_id(accounts(2))
-            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(STORAGE_FOR_MINT)
             .build()
         );
 
-        contract.nft_transfer(accounts(3), token_id.clone(), None, None);
-
-        let token = contract.nft_token(token_id).unwrap();
-        assert_eq!(
-            token.owner_id,
-            accounts(3).to_string()
-        )
+        contract.nft_mint("1".to_string(), accounts(2))
     }
 
     #[test]
-    fn test_nft_transfer_payout() {
+    fn test_nft_is_valid() {
         let (mut context, mut contract) = setup_contract();
-        testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
-            .build()
-        );
-
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
+        let token_id = mint_ticket(&mut context, &mut contract, ExpiryPolicy::Transferable);
 
-        create_series(&mut contract, &royalty, None, None);
+        let token = contract.nft_token(token_id.clone()).unwrap();
+        assert_eq!(token.metadata.unwrap().expires_at, Some((2_000 * 10u64.pow(3)).to_string()));
+        assert!(!contract.nft_is_valid(token_id.clone()));
 
-        testing_env!(context
-            .predecessor_account_id(accounts(1))
-            .attached_deposit(STORAGE_FOR_MINT)
-            .build()
-        );
+        testing_env!(context.block_timestamp(1_500 * 10u64.pow(9)).build());
+        assert!(contract.nft_is_valid(token_id.clone()));
 
-        let token_id = contract.nft_mint("1".to_string(), accounts(2));
+        testing_env!(context.block_timestamp(2_000 * 10u64.pow(9)).build());
+        assert!(!contract.nft_is_valid(token_id.clone()));
 
         testing_env!(context
             .predecessor_account_id(accounts(2))
@@ -1627,139 +5831,108 @@ mod tests {
             .build()
         );
 
-        let payout = contract.nft_transfer_payout(
-            accounts(3),
-            token_id.clone(),
-            Some(0) ,
-            Some(U128::from(1 * 10u128.pow(24))),
-            Some(10)
-        );
-
-        let mut payout_calc: HashMap<AccountId, U128> = HashMap::new();
-        payout_calc.insert(
-            accounts(1).to_string(),
-            U128::from((1000 * (1 * 10u128.pow(24)))/10_000)
-        );
-        payout_calc.insert(
-            accounts(2).to_string(),
-            U128::from((9000 * (1 * 10u128.pow(24))) / 10_000)
-        );
-
-        assert_eq!(payout.unwrap().payout, payout_calc);
-
-        let token = contract.nft_token(token_id).unwrap();
-        assert_eq!(
-            token.owner_id,
-            accounts(3).to_string()
-        )
+        contract.nft_transfer(accounts(3), token_id.clone(), None, None);
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(3).to_string());
     }
 
     #[test]
-    fn test_change_transaction_fee_immediately() {
+    #[should_panic(expected = "Paras: token is expired")]
+    fn test_invalid_transfer_expired() {
         let (mut context, mut contract) = setup_contract();
+        let token_id = mint_ticket(&mut context, &mut contract, ExpiryPolicy::NonTransferable);
 
         testing_env!(context
-            .predecessor_account_id(accounts(0))
+            .predecessor_account_id(accounts(2))
             .attached_deposit(1)
+            .block_timestamp(2_000 * 10u64.pow(9))
             .build()
         );
 
-        contract.set_transaction_fee(100, None);
-
-        assert_eq!(contract.get_transaction_fee().current_fee, 100);
+        contract.nft_transfer(accounts(3), token_id, None, None);
     }
 
     #[test]
-    fn test_change_transaction_fee_with_time() {
+    fn test_burn_expired() {
         let (mut context, mut contract) = setup_contract();
+        let token_id = mint_ticket(&mut context, &mut contract, ExpiryPolicy::CreatorBurnable);
 
         testing_env!(context
-            .predecessor_account_id(accounts(0))
+            .predecessor_account_id(accounts(1))
             .attached_deposit(1)
+            .block_timestamp(2_000 * 10u64.pow(9))
             .build()
         );
 
-        assert_eq!(contract.get_transaction_fee().current_fee, 500);
-        assert_eq!(contract.get_transaction_fee().next_fee, None);
-        assert_eq!(contract.get_transaction_fee().start_time, None);
-
-        let next_fee: u16 = 100;
-        let start_time: Timestamp = 1618109122863866400;
-        let start_time_sec: TimestampSec = to_sec(start_time);
-        contract.set_transaction_fee(next_fee, Some(start_time_sec));
+        contract.nft_burn_expired(vec![token_id.clone()]);
+        assert!(contract.nft_token(token_id).is_none());
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128::from(0));
+    }
 
-        assert_eq!(contract.get_transaction_fee().current_fee, 500);
-        assert_eq!(contract.get_transaction_fee().next_fee, Some(next_fee));
-        assert_eq!(contract.get_transaction_fee().start_time, Some(start_time_sec));
+    #[test]
+    #[should_panic(expected = "Paras: token is not expired : 1:1")]
+    fn test_invalid_burn_before_expiry() {
+        let (mut context, mut contract) = setup_contract();
+        let token_id = mint_ticket(&mut context, &mut contract, ExpiryPolicy::CreatorBurnable);
 
         testing_env!(context
             .predecessor_account_id(accounts(1))
-            .block_timestamp(start_time + 1)
+            .attached_deposit(1)
+            .block_timestamp(1_500 * 10u64.pow(9))
             .build()
         );
 
-        contract.calculate_current_transaction_fee();
-        assert_eq!(contract.get_transaction_fee().current_fee, next_fee);
-        assert_eq!(contract.get_transaction_fee().next_fee, None);
-        assert_eq!(contract.get_transaction_fee().start_time, None);
+        contract.nft_burn_expired(vec![token_id]);
     }
 
     #[test]
-    fn test_transaction_fee_locked() {
+    fn test_claim_failed_ft_payout() {
         let (mut context, mut contract) = setup_contract();
-
-        testing_env!(context
-            .predecessor_account_id(accounts(0))
-            .attached_deposit(1)
-            .build()
+        near_sdk::test_utils::testing_env_with_promise_results(
+            context.predecessor_account_id(accounts(0)).build(),
+            PromiseResult::Failed,
         );
 
-        assert_eq!(contract.get_transaction_fee().current_fee, 500);
-        assert_eq!(contract.get_transaction_fee().next_fee, None);
-        assert_eq!(contract.get_transaction_fee().start_time, None);
-
-        let next_fee: u16 = 100;
-        let start_time: Timestamp = 1618109122863866400;
-        let start_time_sec: TimestampSec = to_sec(start_time);
-        contract.set_transaction_fee(next_fee, Some(start_time_sec));
-
-        let mut royalty: HashMap<AccountId, u32> = HashMap::new();
-        royalty.insert(accounts(1).to_string(), 1000);
+        contract.ft_resolve_payout(accounts(5).to_string(), accounts(1).to_string(), U128::from(1_000));
+        assert_eq!(contract.get_unclaimed_ft_payout(accounts(5), accounts(1)), U128::from(1_000));
 
         testing_env!(context
-            .predecessor_account_id(accounts(0))
-            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
             .build()
         );
 
-        create_series(&mut contract, &royalty, Some(U128::from(1 * 10u128.pow(24))), None);
+        contract.claim_ft_payout(accounts(5));
+        assert_eq!(contract.get_unclaimed_ft_payout(accounts(5), accounts(1)), U128::from(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Paras: cannot change expiry policy, already minted : 1")]
+    fn test_invalid_set_expiry_policy_after_mint() {
+        let (mut context, mut contract) = setup_contract();
+        mint_ticket(&mut context, &mut contract, ExpiryPolicy::Transferable);
 
         testing_env!(context
-            .predecessor_account_id(accounts(0))
+            .predecessor_account_id(accounts(1))
             .attached_deposit(1)
             .build()
         );
 
-        contract.nft_set_series_price("1".to_string(), None);
-
-        assert_eq!(contract.get_transaction_fee().current_fee, 500);
-        assert_eq!(contract.get_transaction_fee().next_fee, Some(next_fee));
-        assert_eq!(contract.get_transaction_fee().start_time, Some(start_time_sec));
+        contract.nft_set_series_expiry_policy("1".to_string(), ExpiryPolicy::CreatorBurnable);
+    }
 
+    #[test]
+    #[should_panic(expected = "Paras: expires_at must be in the future")]
+    fn test_invalid_create_series_already_expired() {
+        let (mut context, mut contract) = setup_contract();
         testing_env!(context
             .predecessor_account_id(accounts(1))
-            .block_timestamp(start_time + 1)
-            .attached_deposit(1)
+            .attached_deposit(STORAGE_FOR_CREATE_SERIES)
+            .block_timestamp(3_000 * 10u64.pow(9))
             .build()
         );
 
-        contract.calculate_current_transaction_fee();
-        assert_eq!(contract.get_transaction_fee().current_fee, next_fee);
-        assert_eq!(contract.get_transaction_fee().next_fee, None);
-        assert_eq!(contract.get_transaction_fee().start_time, None);
-
-        let series = contract.nft_get_series_single("1".to_string());
-        let series_transaction_fee: u128 = series.transaction_fee.0;
-        assert_eq!(series_transaction_fee, 500);
+        let mut token_metadata = sample_token_metadata(None);
+        token_metadata.expires_at = Some((2_000 * 10u64.pow(3)).to_string());
+        contract.nft_create_series(token_metadata, None, None, None, None, None, None, None, None);
     }
 }
@@ -0,0 +1,220 @@
+use crate::*;
+use near_sdk::collections::Vector;
+
+/// layout version written by `new` and `migrate`, bump it and add a step to
+/// `VersionedContract::migrate_step` on every change to the `Contract` layout
+pub const STATE_VERSION: u16 = 3;
+/// version assumed for contracts deployed before the version was stored
+pub const LEGACY_STATE_VERSION: u16 = 2;
+
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+pub fn read_state_version() -> Option<u16> {
+    env::storage_read(STATE_VERSION_KEY).map(|bytes| {
+        let mut buf = [0u8; 2];
+        buf.copy_from_slice(&bytes);
+        u16::from_le_bytes(buf)
+    })
+}
+
+pub fn write_state_version(version: u16) {
+    env::storage_write(STATE_VERSION_KEY, &version.to_le_bytes());
+}
+
+/// reads a collection written with one value type as the same collection with another,
+/// values already stored keep their layout
+fn reinterpret<T: BorshSerialize, U: BorshDeserialize>(from: &T) -> U {
+    U::try_from_slice(&from.try_to_vec().unwrap()).unwrap()
+}
+
+/// (next index, end index) of the series still stored in the TokenSeriesV1 layout
+const SERIES_MIGRATION_KEY: &[u8] = b"SERIES_MIGRATION";
+
+pub fn read_series_migration() -> Option<(u64, u64)> {
+    env::storage_read(SERIES_MIGRATION_KEY).map(|bytes| <(u64, u64)>::try_from_slice(&bytes).unwrap())
+}
+
+fn write_series_migration(from_index: u64, to_index: u64) {
+    if from_index < to_index {
+        env::storage_write(SERIES_MIGRATION_KEY, &(from_index, to_index).try_to_vec().unwrap());
+    } else {
+        env::storage_remove(SERIES_MIGRATION_KEY);
+    }
+}
+
+/// rewrites up to limit series left in the TokenSeriesV1 layout by `migrate`, in place so
+/// every series keeps its index. Series are never removed, and new ones are appended after
+/// the migrated range. Returns the number of series left
+pub fn migrate_token_series(token_series_by_id: &UnorderedMap<TokenSeriesId, TokenSeries>, limit: u64) -> u64 {
+    let (from_index, to_index) = match read_series_migration() {
+        Some(range) => range,
+        None => return 0,
+    };
+    let next_index = std::cmp::min(from_index.saturating_add(limit), to_index);
+
+    let keys = token_series_by_id.keys_as_vector();
+    let mut values: Vector<TokenSeriesV1> = reinterpret(token_series_by_id.values_as_vector());
+    for index in from_index..next_index {
+        let token_series_id = keys.get(index).unwrap();
+        let token_series = migrate_token_series_v1(&token_series_id, values.get(index).unwrap());
+        values.replace_raw(index, &token_series.try_to_vec().unwrap());
+    }
+
+    write_series_migration(next_index, to_index);
+    to_index - next_index
+}
+
+fn migrate_token_series_v1(token_series_id: &TokenSeriesId, token_series: TokenSeriesV1) -> TokenSeries {
+    TokenSeries {
+        metadata: token_series.metadata,
+        creator_id: token_series.creator_id,
+        tokens: token_series.tokens,
+        price: token_series.price,
+        is_mintable: token_series.is_mintable,
+        royalty: token_series.royalty,
+        ft_token_id: None,
+        sale_start: None,
+        sale_end: None,
+        presale_price: None,
+        allowlist: LookupMap::new(
+            StorageKey::SeriesAllowlist {
+                token_series: token_series_id.clone(),
+            }
+            .try_to_vec()
+            .unwrap(),
+        ),
+        max_per_account: None,
+        dutch_auction: None,
+        primary_split: HashMap::new(),
+        is_royalty_locked: false,
+        is_metadata_frozen: false,
+        transferable: true,
+        transfer_lock_sec: None,
+        expiry_policy: ExpiryPolicy::Transferable,
+        is_minter_delegated: false,
+    }
+}
+
+// V1
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TokenSeriesV1 {
+    pub metadata: TokenMetadata,
+    pub creator_id: AccountId,
+    pub tokens: UnorderedSet<TokenId>,
+    pub price: Option<Balance>,
+    pub is_mintable: bool,
+    pub royalty: HashMap<AccountId, u32>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV1 {
+    pub tokens: NonFungibleToken,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub token_series_by_id: UnorderedMap<TokenSeriesId, TokenSeriesV1>,
+    pub treasury_id: AccountId,
+    pub transaction_fee: TransactionFee,
+}
+
+// V2, adds market_data_transaction_fee
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV2 {
+    pub tokens: NonFungibleToken,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub token_series_by_id: UnorderedMap<TokenSeriesId, TokenSeriesV1>,
+    pub treasury_id: AccountId,
+    pub transaction_fee: TransactionFee,
+    pub market_data_transaction_fee: MarketDataTransactionFee,
+}
+
+impl From<ContractV1> for ContractV2 {
+    fn from(prev: ContractV1) -> Self {
+        ContractV2 {
+            tokens: prev.tokens,
+            metadata: prev.metadata,
+            token_series_by_id: prev.token_series_by_id,
+            treasury_id: prev.treasury_id,
+            transaction_fee: prev.transaction_fee,
+            market_data_transaction_fee: MarketDataTransactionFee {
+                transaction_fee: UnorderedMap::new(StorageKey::MarketDataTransactionFee),
+            },
+        }
+    }
+}
+
+// V3, extends TokenSeries and adds storage, auction, role, pause, upgrade, operator,
+// transfer lock, ft payout and storage payer state
+
+impl From<ContractV2> for Contract {
+    fn from(prev: ContractV2) -> Self {
+        // series are rewritten by migrate_token_series in batches, too many to fit in one call
+        write_series_migration(0, prev.token_series_by_id.len());
+        let token_series_by_id = reinterpret(&prev.token_series_by_id);
+
+        Contract {
+            tokens: prev.tokens,
+            metadata: prev.metadata,
+            token_series_by_id,
+            treasury_id: prev.treasury_id,
+            transaction_fee: prev.transaction_fee,
+            market_data_transaction_fee: prev.market_data_transaction_fee,
+            series_mints_by_account: LookupMap::new(StorageKey::SeriesMintsByAccount),
+            auction_by_series_id: LookupMap::new(StorageKey::AuctionBySeriesId),
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            role_members: LookupMap::new(StorageKey::RoleMembers),
+            pending_owner_id: None,
+            paused_features: UnorderedSet::new(StorageKey::PausedFeatures),
+            staged_code: LazyOption::new(StorageKey::StagedCode, None),
+            staged_upgrade: None,
+            upgrade_delay_sec: DEFAULT_UPGRADE_DELAY_SEC,
+            operator_approvals: LookupMap::new(StorageKey::OperatorApprovals),
+            transfer_locked_until: LookupMap::new(StorageKey::TransferLockedUntil),
+            unclaimed_ft_payouts: LookupMap::new(StorageKey::UnclaimedFtPayouts),
+            storage_paid_by_token_id: LookupMap::new(StorageKey::StoragePaidByTokenId),
+        }
+    }
+}
+
+pub enum VersionedContract {
+    V1(ContractV1),
+    V2(ContractV2),
+    Current(Contract),
+}
+
+impl VersionedContract {
+    pub fn read(version: u16) -> Self {
+        match version {
+            1 => VersionedContract::V1(env::state_read().expect("ERR_NOT_INITIALIZED")),
+            2 => VersionedContract::V2(env::state_read().expect("ERR_NOT_INITIALIZED")),
+            STATE_VERSION => VersionedContract::Current(env::state_read().expect("ERR_NOT_INITIALIZED")),
+            _ => env::panic(format!("Paras: unknown state version {}", version).as_bytes()),
+        }
+    }
+
+    pub fn owner_id(&self) -> &AccountId {
+        match self {
+            VersionedContract::V1(state) => &state.tokens.owner_id,
+            VersionedContract::V2(state) => &state.tokens.owner_id,
+            VersionedContract::Current(state) => &state.tokens.owner_id,
+        }
+    }
+
+    fn migrate_step(self) -> Self {
+        match self {
+            VersionedContract::V1(state) => VersionedContract::V2(state.into()),
+            VersionedContract::V2(state) => VersionedContract::Current(state.into()),
+            VersionedContract::Current(state) => VersionedContract::Current(state),
+        }
+    }
+
+    pub fn into_current(self) -> Contract {
+        let mut state = self;
+        loop {
+            match state {
+                VersionedContract::Current(contract) => return contract,
+                prev => state = prev.migrate_step(),
+            }
+        }
+    }
+}